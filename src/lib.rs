@@ -1,4 +1,24 @@
-use libc;
+#![allow(non_camel_case_types)]
+// The documentation below is carried over from libretro.h as-is.
+#![allow(
+    clippy::doc_lazy_continuation,
+    clippy::doc_overindented_list_items,
+    clippy::empty_line_after_doc_comments,
+    clippy::tabs_in_doc_comments
+)]
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+#[cfg(feature = "loader")]
+mod loader;
+#[cfg(feature = "loader")]
+pub use loader::{CoreApi, CoreInfo, LoadError};
 
 /// Used for checking API/ABI mismatches that can break libretro implementations
 /// It is not incremented for compatible changes to the API.
@@ -16,7 +36,15 @@ pub const API_VERSION: libc::c_uint = 1;
 /// correctly to arbitrary controller layouts.
 pub const DEVICE_TYPE_SHIFT: libc::c_uint = 8;
 pub const DEVICE_MASK: libc::c_uint = (1 << DEVICE_TYPE_SHIFT) - 1;
-// todo: device subclas
+
+/// Builds the id of a device subclass, as RETRO_DEVICE_SUBCLASS does.
+/// The result masked with DEVICE_MASK is the base device.
+///
+/// Only ids below `c_uint::MAX >> DEVICE_TYPE_SHIFT` produce distinct
+/// subclasses; larger ids wrap like the C macro instead of overflowing.
+pub const fn device_subclass(base: libc::c_uint, id: libc::c_uint) -> libc::c_uint {
+    (id.wrapping_add(1) << DEVICE_TYPE_SHIFT) | base
+}
 
 /// Input disabled
 pub const DEVICE_NONE: libc::c_uint = 0;
@@ -170,7 +198,147 @@ pub const DEVICE_ID_POINTER_COUNT: libc::c_uint = 3;
 pub const REGION_NTSC: libc::c_uint = 0;
 pub const REGION_PAL: libc::c_uint = 1;
 
-// todo: enum retro_language
+/// Language values returned by ENVIRONMENT_GET_LANGUAGE.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    English = 0,
+    Japanese = 1,
+    French = 2,
+    Spanish = 3,
+    German = 4,
+    Italian = 5,
+    Dutch = 6,
+    PortugueseBrazil = 7,
+    PortuguesePortugal = 8,
+    Russian = 9,
+    Korean = 10,
+    ChineseTraditional = 11,
+    ChineseSimplified = 12,
+    Esperanto = 13,
+    Polish = 14,
+    Vietnamese = 15,
+    Arabic = 16,
+    Greek = 17,
+    Turkish = 18,
+    Slovak = 19,
+    Persian = 20,
+    Hebrew = 21,
+    Asturian = 22,
+    Finnish = 23,
+    Indonesian = 24,
+    Swedish = 25,
+    Ukrainian = 26,
+    Czech = 27,
+    CatalanValencia = 28,
+    Catalan = 29,
+    BritishEnglish = 30,
+    Hungarian = 31,
+    Belarusian = 32,
+    Galician = 33,
+    Norwegian = 34,
+    Last = 35,
+
+    /// Ensure sizeof(enum) == sizeof(int)
+    Dummy = i32::MAX as isize,
+}
+
+const LANGUAGES: [Language; Language::Last as usize] = [
+    Language::English,
+    Language::Japanese,
+    Language::French,
+    Language::Spanish,
+    Language::German,
+    Language::Italian,
+    Language::Dutch,
+    Language::PortugueseBrazil,
+    Language::PortuguesePortugal,
+    Language::Russian,
+    Language::Korean,
+    Language::ChineseTraditional,
+    Language::ChineseSimplified,
+    Language::Esperanto,
+    Language::Polish,
+    Language::Vietnamese,
+    Language::Arabic,
+    Language::Greek,
+    Language::Turkish,
+    Language::Slovak,
+    Language::Persian,
+    Language::Hebrew,
+    Language::Asturian,
+    Language::Finnish,
+    Language::Indonesian,
+    Language::Swedish,
+    Language::Ukrainian,
+    Language::Czech,
+    Language::CatalanValencia,
+    Language::Catalan,
+    Language::BritishEnglish,
+    Language::Hungarian,
+    Language::Belarusian,
+    Language::Galician,
+    Language::Norwegian,
+];
+
+impl Language {
+    /// Closest BCP-47 tag for the language, "und" for `Last`/`Dummy`.
+    pub fn as_bcp47(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Japanese => "ja",
+            Language::French => "fr",
+            Language::Spanish => "es",
+            Language::German => "de",
+            Language::Italian => "it",
+            Language::Dutch => "nl",
+            Language::PortugueseBrazil => "pt-BR",
+            Language::PortuguesePortugal => "pt-PT",
+            Language::Russian => "ru",
+            Language::Korean => "ko",
+            Language::ChineseTraditional => "zh-Hant",
+            Language::ChineseSimplified => "zh-Hans",
+            Language::Esperanto => "eo",
+            Language::Polish => "pl",
+            Language::Vietnamese => "vi",
+            Language::Arabic => "ar",
+            Language::Greek => "el",
+            Language::Turkish => "tr",
+            Language::Slovak => "sk",
+            Language::Persian => "fa",
+            Language::Hebrew => "he",
+            Language::Asturian => "ast",
+            Language::Finnish => "fi",
+            Language::Indonesian => "id",
+            Language::Swedish => "sv",
+            Language::Ukrainian => "uk",
+            Language::Czech => "cs",
+            Language::CatalanValencia => "ca-ES-valencia",
+            Language::Catalan => "ca",
+            Language::BritishEnglish => "en-GB",
+            Language::Hungarian => "hu",
+            Language::Belarusian => "be",
+            Language::Galician => "gl",
+            Language::Norwegian => "no",
+            Language::Last | Language::Dummy => "und",
+        }
+    }
+}
+
+impl TryFrom<libc::c_uint> for Language {
+    type Error = libc::c_uint;
+
+    /// Rejects `Last`, `Dummy` and anything unknown to this crate.
+    fn try_from(value: libc::c_uint) -> Result<Self, Self::Error> {
+        LANGUAGES.get(value as usize).copied().ok_or(value)
+    }
+}
+
+impl From<Language> for libc::c_uint {
+    fn from(language: Language) -> Self {
+        language as libc::c_uint
+    }
+}
 
 /// Passed to retro_get_memory_data/size().
 /// If the memory type doesn't apply to the
@@ -194,8 +362,517 @@ pub const MEMORY_SYSTEM_RAM: libc::c_uint = 2;
 /// Video ram lets a frontend peek into a game systems video RAM (VRAM).
 pub const MEMORY_VIDEO_RAM: libc::c_uint = 3;
 
-// todo: retro_key
-// todo: retro_mod
+/// Keyboard key codes, as passed to retro_keyboard_event_t and used as
+/// ids for the KEYBOARD device.
+/// Values are sparse and follow ASCII where possible.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// RETROK_UNKNOWN
+    Unknown = 0,
+    /// RETROK_BACKSPACE
+    Backspace = 8,
+    /// RETROK_TAB
+    Tab = 9,
+    /// RETROK_CLEAR
+    Clear = 12,
+    /// RETROK_RETURN
+    Return = 13,
+    /// RETROK_PAUSE
+    Pause = 19,
+    /// RETROK_ESCAPE
+    Escape = 27,
+    /// RETROK_SPACE
+    Space = 32,
+    /// RETROK_EXCLAIM
+    Exclaim = 33,
+    /// RETROK_QUOTEDBL
+    QuoteDbl = 34,
+    /// RETROK_HASH
+    Hash = 35,
+    /// RETROK_DOLLAR
+    Dollar = 36,
+    /// RETROK_AMPERSAND
+    Ampersand = 38,
+    /// RETROK_QUOTE
+    Quote = 39,
+    /// RETROK_LEFTPAREN
+    LeftParen = 40,
+    /// RETROK_RIGHTPAREN
+    RightParen = 41,
+    /// RETROK_ASTERISK
+    Asterisk = 42,
+    /// RETROK_PLUS
+    Plus = 43,
+    /// RETROK_COMMA
+    Comma = 44,
+    /// RETROK_MINUS
+    Minus = 45,
+    /// RETROK_PERIOD
+    Period = 46,
+    /// RETROK_SLASH
+    Slash = 47,
+    /// RETROK_0
+    Num0 = 48,
+    /// RETROK_1
+    Num1 = 49,
+    /// RETROK_2
+    Num2 = 50,
+    /// RETROK_3
+    Num3 = 51,
+    /// RETROK_4
+    Num4 = 52,
+    /// RETROK_5
+    Num5 = 53,
+    /// RETROK_6
+    Num6 = 54,
+    /// RETROK_7
+    Num7 = 55,
+    /// RETROK_8
+    Num8 = 56,
+    /// RETROK_9
+    Num9 = 57,
+    /// RETROK_COLON
+    Colon = 58,
+    /// RETROK_SEMICOLON
+    Semicolon = 59,
+    /// RETROK_LESS
+    Less = 60,
+    /// RETROK_EQUALS
+    Equals = 61,
+    /// RETROK_GREATER
+    Greater = 62,
+    /// RETROK_QUESTION
+    Question = 63,
+    /// RETROK_AT
+    At = 64,
+    /// RETROK_LEFTBRACKET
+    LeftBracket = 91,
+    /// RETROK_BACKSLASH
+    Backslash = 92,
+    /// RETROK_RIGHTBRACKET
+    RightBracket = 93,
+    /// RETROK_CARET
+    Caret = 94,
+    /// RETROK_UNDERSCORE
+    Underscore = 95,
+    /// RETROK_BACKQUOTE
+    Backquote = 96,
+    /// RETROK_a
+    A = 97,
+    /// RETROK_b
+    B = 98,
+    /// RETROK_c
+    C = 99,
+    /// RETROK_d
+    D = 100,
+    /// RETROK_e
+    E = 101,
+    /// RETROK_f
+    F = 102,
+    /// RETROK_g
+    G = 103,
+    /// RETROK_h
+    H = 104,
+    /// RETROK_i
+    I = 105,
+    /// RETROK_j
+    J = 106,
+    /// RETROK_k
+    K = 107,
+    /// RETROK_l
+    L = 108,
+    /// RETROK_m
+    M = 109,
+    /// RETROK_n
+    N = 110,
+    /// RETROK_o
+    O = 111,
+    /// RETROK_p
+    P = 112,
+    /// RETROK_q
+    Q = 113,
+    /// RETROK_r
+    R = 114,
+    /// RETROK_s
+    S = 115,
+    /// RETROK_t
+    T = 116,
+    /// RETROK_u
+    U = 117,
+    /// RETROK_v
+    V = 118,
+    /// RETROK_w
+    W = 119,
+    /// RETROK_x
+    X = 120,
+    /// RETROK_y
+    Y = 121,
+    /// RETROK_z
+    Z = 122,
+    /// RETROK_LEFTBRACE
+    LeftBrace = 123,
+    /// RETROK_BAR
+    Bar = 124,
+    /// RETROK_RIGHTBRACE
+    RightBrace = 125,
+    /// RETROK_TILDE
+    Tilde = 126,
+    /// RETROK_DELETE
+    Delete = 127,
+
+    /// RETROK_KP0
+    Kp0 = 256,
+    /// RETROK_KP1
+    Kp1 = 257,
+    /// RETROK_KP2
+    Kp2 = 258,
+    /// RETROK_KP3
+    Kp3 = 259,
+    /// RETROK_KP4
+    Kp4 = 260,
+    /// RETROK_KP5
+    Kp5 = 261,
+    /// RETROK_KP6
+    Kp6 = 262,
+    /// RETROK_KP7
+    Kp7 = 263,
+    /// RETROK_KP8
+    Kp8 = 264,
+    /// RETROK_KP9
+    Kp9 = 265,
+    /// RETROK_KP_PERIOD
+    KpPeriod = 266,
+    /// RETROK_KP_DIVIDE
+    KpDivide = 267,
+    /// RETROK_KP_MULTIPLY
+    KpMultiply = 268,
+    /// RETROK_KP_MINUS
+    KpMinus = 269,
+    /// RETROK_KP_PLUS
+    KpPlus = 270,
+    /// RETROK_KP_ENTER
+    KpEnter = 271,
+    /// RETROK_KP_EQUALS
+    KpEquals = 272,
+    /// RETROK_UP
+    Up = 273,
+    /// RETROK_DOWN
+    Down = 274,
+    /// RETROK_RIGHT
+    Right = 275,
+    /// RETROK_LEFT
+    Left = 276,
+    /// RETROK_INSERT
+    Insert = 277,
+    /// RETROK_HOME
+    Home = 278,
+    /// RETROK_END
+    End = 279,
+    /// RETROK_PAGEUP
+    PageUp = 280,
+    /// RETROK_PAGEDOWN
+    PageDown = 281,
+    /// RETROK_F1
+    F1 = 282,
+    /// RETROK_F2
+    F2 = 283,
+    /// RETROK_F3
+    F3 = 284,
+    /// RETROK_F4
+    F4 = 285,
+    /// RETROK_F5
+    F5 = 286,
+    /// RETROK_F6
+    F6 = 287,
+    /// RETROK_F7
+    F7 = 288,
+    /// RETROK_F8
+    F8 = 289,
+    /// RETROK_F9
+    F9 = 290,
+    /// RETROK_F10
+    F10 = 291,
+    /// RETROK_F11
+    F11 = 292,
+    /// RETROK_F12
+    F12 = 293,
+    /// RETROK_F13
+    F13 = 294,
+    /// RETROK_F14
+    F14 = 295,
+    /// RETROK_F15
+    F15 = 296,
+    /// RETROK_NUMLOCK
+    NumLock = 300,
+    /// RETROK_CAPSLOCK
+    CapsLock = 301,
+    /// RETROK_SCROLLOCK
+    ScrollLock = 302,
+    /// RETROK_RSHIFT
+    RShift = 303,
+    /// RETROK_LSHIFT
+    LShift = 304,
+    /// RETROK_RCTRL
+    RCtrl = 305,
+    /// RETROK_LCTRL
+    LCtrl = 306,
+    /// RETROK_RALT
+    RAlt = 307,
+    /// RETROK_LALT
+    LAlt = 308,
+    /// RETROK_RMETA
+    RMeta = 309,
+    /// RETROK_LMETA
+    LMeta = 310,
+    /// RETROK_LSUPER
+    LSuper = 311,
+    /// RETROK_RSUPER
+    RSuper = 312,
+    /// RETROK_MODE
+    Mode = 313,
+    /// RETROK_COMPOSE
+    Compose = 314,
+    /// RETROK_HELP
+    Help = 315,
+    /// RETROK_PRINT
+    Print = 316,
+    /// RETROK_SYSREQ
+    SysReq = 317,
+    /// RETROK_BREAK
+    Break = 318,
+    /// RETROK_MENU
+    Menu = 319,
+    /// RETROK_POWER
+    Power = 320,
+    /// RETROK_EURO
+    Euro = 321,
+    /// RETROK_UNDO
+    Undo = 322,
+    /// RETROK_OEM_102
+    Oem102 = 323,
+
+    Last = 324,
+
+    /// Ensure sizeof(enum) == sizeof(int)
+    Dummy = i32::MAX as isize,
+}
+
+impl Key {
+    /// Decodes a RETROK_* value, `None` for codes libretro.h doesn't define.
+    pub fn from_code(code: libc::c_uint) -> Option<Key> {
+        Some(match code {
+            0 => Key::Unknown,
+            8 => Key::Backspace,
+            9 => Key::Tab,
+            12 => Key::Clear,
+            13 => Key::Return,
+            19 => Key::Pause,
+            27 => Key::Escape,
+            32 => Key::Space,
+            33 => Key::Exclaim,
+            34 => Key::QuoteDbl,
+            35 => Key::Hash,
+            36 => Key::Dollar,
+            38 => Key::Ampersand,
+            39 => Key::Quote,
+            40 => Key::LeftParen,
+            41 => Key::RightParen,
+            42 => Key::Asterisk,
+            43 => Key::Plus,
+            44 => Key::Comma,
+            45 => Key::Minus,
+            46 => Key::Period,
+            47 => Key::Slash,
+            48 => Key::Num0,
+            49 => Key::Num1,
+            50 => Key::Num2,
+            51 => Key::Num3,
+            52 => Key::Num4,
+            53 => Key::Num5,
+            54 => Key::Num6,
+            55 => Key::Num7,
+            56 => Key::Num8,
+            57 => Key::Num9,
+            58 => Key::Colon,
+            59 => Key::Semicolon,
+            60 => Key::Less,
+            61 => Key::Equals,
+            62 => Key::Greater,
+            63 => Key::Question,
+            64 => Key::At,
+            91 => Key::LeftBracket,
+            92 => Key::Backslash,
+            93 => Key::RightBracket,
+            94 => Key::Caret,
+            95 => Key::Underscore,
+            96 => Key::Backquote,
+            97 => Key::A,
+            98 => Key::B,
+            99 => Key::C,
+            100 => Key::D,
+            101 => Key::E,
+            102 => Key::F,
+            103 => Key::G,
+            104 => Key::H,
+            105 => Key::I,
+            106 => Key::J,
+            107 => Key::K,
+            108 => Key::L,
+            109 => Key::M,
+            110 => Key::N,
+            111 => Key::O,
+            112 => Key::P,
+            113 => Key::Q,
+            114 => Key::R,
+            115 => Key::S,
+            116 => Key::T,
+            117 => Key::U,
+            118 => Key::V,
+            119 => Key::W,
+            120 => Key::X,
+            121 => Key::Y,
+            122 => Key::Z,
+            123 => Key::LeftBrace,
+            124 => Key::Bar,
+            125 => Key::RightBrace,
+            126 => Key::Tilde,
+            127 => Key::Delete,
+            256 => Key::Kp0,
+            257 => Key::Kp1,
+            258 => Key::Kp2,
+            259 => Key::Kp3,
+            260 => Key::Kp4,
+            261 => Key::Kp5,
+            262 => Key::Kp6,
+            263 => Key::Kp7,
+            264 => Key::Kp8,
+            265 => Key::Kp9,
+            266 => Key::KpPeriod,
+            267 => Key::KpDivide,
+            268 => Key::KpMultiply,
+            269 => Key::KpMinus,
+            270 => Key::KpPlus,
+            271 => Key::KpEnter,
+            272 => Key::KpEquals,
+            273 => Key::Up,
+            274 => Key::Down,
+            275 => Key::Right,
+            276 => Key::Left,
+            277 => Key::Insert,
+            278 => Key::Home,
+            279 => Key::End,
+            280 => Key::PageUp,
+            281 => Key::PageDown,
+            282 => Key::F1,
+            283 => Key::F2,
+            284 => Key::F3,
+            285 => Key::F4,
+            286 => Key::F5,
+            287 => Key::F6,
+            288 => Key::F7,
+            289 => Key::F8,
+            290 => Key::F9,
+            291 => Key::F10,
+            292 => Key::F11,
+            293 => Key::F12,
+            294 => Key::F13,
+            295 => Key::F14,
+            296 => Key::F15,
+            300 => Key::NumLock,
+            301 => Key::CapsLock,
+            302 => Key::ScrollLock,
+            303 => Key::RShift,
+            304 => Key::LShift,
+            305 => Key::RCtrl,
+            306 => Key::LCtrl,
+            307 => Key::RAlt,
+            308 => Key::LAlt,
+            309 => Key::RMeta,
+            310 => Key::LMeta,
+            311 => Key::LSuper,
+            312 => Key::RSuper,
+            313 => Key::Mode,
+            314 => Key::Compose,
+            315 => Key::Help,
+            316 => Key::Print,
+            317 => Key::SysReq,
+            318 => Key::Break,
+            319 => Key::Menu,
+            320 => Key::Power,
+            321 => Key::Euro,
+            322 => Key::Undo,
+            323 => Key::Oem102,
+            _ => return None,
+        })
+    }
+}
+
+impl From<Key> for libc::c_uint {
+    fn from(key: Key) -> Self {
+        key as libc::c_uint
+    }
+}
+
+/// Keyboard modifier mask, as passed in the key_modifiers argument of
+/// retro_keyboard_event_t.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct KeyMod(pub u16);
+
+impl KeyMod {
+    pub const NONE: KeyMod = KeyMod(0x0000);
+
+    pub const SHIFT: KeyMod = KeyMod(0x01);
+    pub const CTRL: KeyMod = KeyMod(0x02);
+    pub const ALT: KeyMod = KeyMod(0x04);
+    pub const META: KeyMod = KeyMod(0x08);
+
+    pub const NUMLOCK: KeyMod = KeyMod(0x10);
+    pub const CAPSLOCK: KeyMod = KeyMod(0x20);
+    pub const SCROLLOCK: KeyMod = KeyMod(0x40);
+
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// True if every bit set in `other` is also set in `self`.
+    pub const fn contains(self, other: KeyMod) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for KeyMod {
+    type Output = KeyMod;
+
+    fn bitor(self, rhs: KeyMod) -> KeyMod {
+        KeyMod(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for KeyMod {
+    fn bitor_assign(&mut self, rhs: KeyMod) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl std::ops::BitAnd for KeyMod {
+    type Output = KeyMod;
+
+    fn bitand(self, rhs: KeyMod) -> KeyMod {
+        KeyMod(self.0 & rhs.0)
+    }
+}
+
+impl From<u16> for KeyMod {
+    fn from(bits: u16) -> Self {
+        KeyMod(bits)
+    }
+}
 
 /// If set, this call is not part of the public libretro API yet. It can
 /// change or be removed at any time.
@@ -1392,3 +2069,4983 @@ pub const ENVIRONMENT_GET_SAVESTATE_CONTEXT: libc::c_uint = 72 | ENVIRONMENT_EXP
 /// All future updates to the context negotiation interface implies that frontend must support
 /// this environment call to query support.
 pub const ENVIRONMENT_GET_HW_RENDER_CONTEXT_NEGOTIATION_INTERFACE_SUPPORT: libc::c_uint = 73 | ENVIRONMENT_EXPERIMENTAL;
+
+/// Describes how the libretro implementation maps a libretro input bind
+/// to its internal input system through a human readable string.
+/// This string can be used to better let a user configure input.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct InputDescriptor {
+    /// Associates given parameters with a description.
+    pub port: libc::c_uint,
+    pub device: libc::c_uint,
+    pub index: libc::c_uint,
+    pub id: libc::c_uint,
+
+    /// Human readable description for parameters.
+    /// The pointer must remain valid until
+    /// retro_unload_game() is called.
+    pub description: *const libc::c_char,
+}
+
+/// Maps each RetroPad button id described for `port` to its label.
+/// Joypad subclasses count as joypads. Descriptors for other ports or
+/// non-joypad devices are skipped, and
+/// iteration stops at the NULL-description terminator.
+///
+/// # Safety
+/// Every non-NULL `description` must point to a valid C string that
+/// outlives `descs`.
+pub unsafe fn group_descriptors_by_button(
+    descs: &[InputDescriptor],
+    port: libc::c_uint,
+) -> BTreeMap<libc::c_uint, &CStr> {
+    descs
+        .iter()
+        .take_while(|desc| !desc.description.is_null())
+        .filter(|desc| desc.port == port && desc.device & DEVICE_MASK == DEVICE_JOYPAD)
+        .map(|desc| (desc.id, CStr::from_ptr(desc.description)))
+        .collect()
+}
+
+/// Owned `(joypad id, label)` pairs for `port`, ordered by button id,
+/// as consumed by touch overlay builders.
+///
+/// # Safety
+/// Same requirements as [`group_descriptors_by_button`].
+pub unsafe fn overlay_hints(descs: &[InputDescriptor], port: libc::c_uint) -> Vec<(libc::c_uint, String)> {
+    group_descriptors_by_button(descs, port)
+        .into_iter()
+        .map(|(id, label)| (id, label.to_string_lossy().into_owned()))
+        .collect()
+}
+
+/// Formats a list of content extensions the way
+/// retro_system_info::valid_extensions expects them:
+/// lowercase, without leading dots, and '|' delimited.
+/// Empty and repeated entries are dropped.
+pub fn normalize_extensions(exts: &[&str]) -> CString {
+    let mut normalized: Vec<String> = Vec::with_capacity(exts.len());
+    for ext in exts {
+        let ext: String = ext
+            .trim()
+            .trim_start_matches('.')
+            .chars()
+            .filter(|&c| c != '\0')
+            .flat_map(char::to_lowercase)
+            .collect();
+        if !ext.is_empty() && !normalized.contains(&ext) {
+            normalized.push(ext);
+        }
+    }
+    CString::new(normalized.join("|")).expect("interior NUL bytes are filtered out")
+}
+
+/// Snapshot of a LIGHTGUN device, read with a single pass over its ids
+/// through a retro_input_state_t style callback
+/// `(port, device, index, id) -> i16`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LightgunState {
+    screen_x: i16,
+    screen_y: i16,
+    offscreen: bool,
+    trigger: bool,
+    reload: bool,
+    aux_a: bool,
+    aux_b: bool,
+    aux_c: bool,
+    start: bool,
+    select: bool,
+    dpad_up: bool,
+    dpad_down: bool,
+    dpad_left: bool,
+    dpad_right: bool,
+}
+
+impl LightgunState {
+    pub fn read<F>(port: libc::c_uint, mut input_state: F) -> Self
+    where
+        F: FnMut(libc::c_uint, libc::c_uint, libc::c_uint, libc::c_uint) -> i16,
+    {
+        let mut get = |id| input_state(port, DEVICE_LIGHTGUN, 0, id);
+        Self {
+            screen_x: get(DEVICE_ID_LIGHTGUN_SCREEN_X),
+            screen_y: get(DEVICE_ID_LIGHTGUN_SCREEN_Y),
+            offscreen: get(DEVICE_ID_LIGHTGUN_IS_OFFSCREEN) != 0,
+            trigger: get(DEVICE_ID_LIGHTGUN_TRIGGER) != 0,
+            reload: get(DEVICE_ID_LIGHTGUN_RELOAD) != 0,
+            aux_a: get(DEVICE_ID_LIGHTGUN_AUX_A) != 0,
+            aux_b: get(DEVICE_ID_LIGHTGUN_AUX_B) != 0,
+            aux_c: get(DEVICE_ID_LIGHTGUN_AUX_C) != 0,
+            start: get(DEVICE_ID_LIGHTGUN_START) != 0,
+            select: get(DEVICE_ID_LIGHTGUN_SELECT) != 0,
+            dpad_up: get(DEVICE_ID_LIGHTGUN_DPAD_UP) != 0,
+            dpad_down: get(DEVICE_ID_LIGHTGUN_DPAD_DOWN) != 0,
+            dpad_left: get(DEVICE_ID_LIGHTGUN_DPAD_LEFT) != 0,
+            dpad_right: get(DEVICE_ID_LIGHTGUN_DPAD_RIGHT) != 0,
+        }
+    }
+
+    pub fn trigger(&self) -> bool {
+        self.trigger
+    }
+
+    pub fn reload(&self) -> bool {
+        self.reload
+    }
+
+    pub fn offscreen(&self) -> bool {
+        self.offscreen
+    }
+
+    /// Screen space coordinates in [-0x8000, 0x7fff],
+    /// or None while the gun points off screen.
+    pub fn screen_pos(&self) -> Option<(i16, i16)> {
+        if self.offscreen {
+            None
+        } else {
+            Some((self.screen_x, self.screen_y))
+        }
+    }
+
+    pub fn aux_a(&self) -> bool {
+        self.aux_a
+    }
+
+    pub fn aux_b(&self) -> bool {
+        self.aux_b
+    }
+
+    pub fn aux_c(&self) -> bool {
+        self.aux_c
+    }
+
+    pub fn start(&self) -> bool {
+        self.start
+    }
+
+    pub fn select(&self) -> bool {
+        self.select
+    }
+
+    pub fn dpad_up(&self) -> bool {
+        self.dpad_up
+    }
+
+    pub fn dpad_down(&self) -> bool {
+        self.dpad_down
+    }
+
+    pub fn dpad_left(&self) -> bool {
+        self.dpad_left
+    }
+
+    pub fn dpad_right(&self) -> bool {
+        self.dpad_right
+    }
+}
+
+/// Raw value returned by the perf interface's get_perf_counter.
+pub type retro_perf_tick_t = u64;
+
+/// Ticks between two perf counter reads.
+/// The counter may wrap around, so the difference is taken modulo 2^64.
+pub fn ticks_elapsed(start: retro_perf_tick_t, end: retro_perf_tick_t) -> u64 {
+    end.wrapping_sub(start)
+}
+
+type AudioGenerator = Box<dyn FnMut() + Send>;
+
+static ASYNC_AUDIO: Mutex<Option<AudioGenerator>> = Mutex::new(None);
+
+/// Owner of the audio generation closure driven by SET_AUDIO_CALLBACK.
+/// The frontend may invoke the callback from any thread, so the closure
+/// must be `Send` and is only ever run while holding a lock.
+/// The closure is expected to call the normal audio callbacks itself.
+pub struct AsyncAudio;
+
+impl AsyncAudio {
+    /// Installs the closure run by [`AsyncAudio::trampoline`],
+    /// replacing any previous one.
+    pub fn set<F>(generate: F)
+    where
+        F: FnMut() + Send + 'static,
+    {
+        *ASYNC_AUDIO.lock().unwrap_or_else(PoisonError::into_inner) = Some(Box::new(generate));
+    }
+
+    /// Removes the installed closure; later callbacks do nothing.
+    pub fn clear() {
+        *ASYNC_AUDIO.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    /// Function to hand to the frontend as retro_audio_callback::callback.
+    ///
+    /// The closure runs with the lock held, so it must not call
+    /// [`AsyncAudio::set`] or [`AsyncAudio::clear`]: either would deadlock.
+    pub extern "C" fn trampoline() {
+        let mut generate = ASYNC_AUDIO.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(generate) = generate.as_mut() {
+            generate();
+        }
+    }
+}
+
+/// Pixel format used by retro_video_refresh_t,
+/// set with ENVIRONMENT_SET_PIXEL_FORMAT.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 0RGB1555, native endian.
+    /// 0 bit must be set to 0.
+    /// This pixel format is default for compatibility concerns only.
+    /// If a 15/16-bit pixel format is desired, consider using RGB565.
+    Format0RGB1555 = 0,
+
+    /// XRGB8888, native endian.
+    /// X bits are ignored.
+    FormatXRGB8888 = 1,
+
+    /// RGB565, native endian.
+    /// This pixel format is the recommended format to use if a 15/16-bit
+    /// format is desired as it is the pixel format that is typically
+    /// available on a wide range of low-power devices.
+    ///
+    /// It is also natively supported in APIs like OpenGL ES.
+    FormatRGB565 = 2,
+
+    /// Ensure sizeof() == sizeof(int).
+    Unknown = i32::MAX as isize,
+}
+
+impl PixelFormat {
+    /// Size of one pixel in bytes, 0 for `Unknown`.
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Format0RGB1555 => 2,
+            PixelFormat::FormatXRGB8888 => 4,
+            PixelFormat::FormatRGB565 => 2,
+            PixelFormat::Unknown => 0,
+        }
+    }
+}
+
+impl TryFrom<libc::c_uint> for PixelFormat {
+    type Error = libc::c_uint;
+
+    /// Only the three real formats are accepted; the `Unknown`
+    /// sentinel is rejected like any other value.
+    fn try_from(value: libc::c_uint) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(PixelFormat::Format0RGB1555),
+            1 => Ok(PixelFormat::FormatXRGB8888),
+            2 => Ok(PixelFormat::FormatRGB565),
+            _ => Err(value),
+        }
+    }
+}
+
+impl From<PixelFormat> for libc::c_uint {
+    fn from(format: PixelFormat) -> Self {
+        format as libc::c_uint
+    }
+}
+
+/// Snapshot of a MOUSE device, read with a single pass over its ids
+/// through a retro_input_state_t style callback
+/// `(port, device, index, id) -> i16`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MouseState {
+    x: i16,
+    y: i16,
+    left: bool,
+    right: bool,
+    middle: bool,
+    button_4: bool,
+    button_5: bool,
+    wheel_up: bool,
+    wheel_down: bool,
+}
+
+impl MouseState {
+    pub fn read<F>(port: libc::c_uint, mut input_state: F) -> Self
+    where
+        F: FnMut(libc::c_uint, libc::c_uint, libc::c_uint, libc::c_uint) -> i16,
+    {
+        let mut get = |id| input_state(port, DEVICE_MOUSE, 0, id);
+        Self {
+            x: get(DEVICE_ID_MOUSE_X),
+            y: get(DEVICE_ID_MOUSE_Y),
+            left: get(DEVICE_ID_MOUSE_LEFT) != 0,
+            right: get(DEVICE_ID_MOUSE_RIGHT) != 0,
+            middle: get(DEVICE_ID_MOUSE_MIDDLE) != 0,
+            button_4: get(DEVICE_ID_MOUSE_BUTTON_4) != 0,
+            button_5: get(DEVICE_ID_MOUSE_BUTTON_5) != 0,
+            wheel_up: get(DEVICE_ID_MOUSE_WHEELUP) != 0,
+            wheel_down: get(DEVICE_ID_MOUSE_WHEELDOWN) != 0,
+        }
+    }
+
+    /// Movement relative to the last poll.
+    pub fn delta(&self) -> (i16, i16) {
+        (self.x, self.y)
+    }
+
+    pub fn left(&self) -> bool {
+        self.left
+    }
+
+    pub fn right(&self) -> bool {
+        self.right
+    }
+
+    pub fn middle(&self) -> bool {
+        self.middle
+    }
+
+    /// 1 when scrolled up, -1 when scrolled down, 0 otherwise.
+    pub fn wheel(&self) -> i16 {
+        self.wheel_up as i16 - self.wheel_down as i16
+    }
+
+    pub fn button4(&self) -> bool {
+        self.button_4
+    }
+
+    pub fn button5(&self) -> bool {
+        self.button_5
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GameGeometry {
+    /// Nominal video width of game.
+    pub base_width: libc::c_uint,
+
+    /// Nominal video height of game.
+    pub base_height: libc::c_uint,
+
+    /// Maximum possible width of game.
+    pub max_width: libc::c_uint,
+
+    /// Maximum possible height of game.
+    pub max_height: libc::c_uint,
+
+    /// Nominal aspect ratio of game. If
+    /// aspect_ratio is <= 0.0, an aspect ratio
+    /// of base_width / base_height is assumed.
+    /// A frontend could override this setting,
+    /// if desired.
+    pub aspect_ratio: libc::c_float,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SystemTiming {
+    /// FPS of video content.
+    pub fps: f64,
+
+    /// Sampling rate of audio.
+    pub sample_rate: f64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SystemAvInfo {
+    pub geometry: GameGeometry,
+    pub timing: SystemTiming,
+}
+
+impl SystemAvInfo {
+    pub fn geometry(&self) -> &GameGeometry {
+        &self.geometry
+    }
+
+    pub fn geometry_mut(&mut self) -> &mut GameGeometry {
+        &mut self.geometry
+    }
+
+    pub fn timing(&self) -> &SystemTiming {
+        &self.timing
+    }
+
+    pub fn timing_mut(&mut self) -> &mut SystemTiming {
+        &mut self.timing
+    }
+}
+
+/// Worst-case size in bytes of a software framebuffer for `info`,
+/// sized from the maximum rather than the nominal geometry.
+/// Saturates at usize::MAX instead of overflowing.
+pub fn framebuffer_size_bytes(info: &SystemAvInfo, fmt: PixelFormat) -> usize {
+    (info.geometry.max_width as usize)
+        .saturating_mul(info.geometry.max_height as usize)
+        .saturating_mul(fmt.bytes_per_pixel())
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GameInfo {
+    /// Path to game, UTF-8 encoded.
+    /// Sometimes used as a reference for building other paths.
+    /// May be NULL if game was loaded from stdin or similar,
+    /// but in this case some cores will be unable to load `data`.
+    /// So, it is preferable to fabricate something here instead
+    /// of passing NULL, which will help more cores to succeed.
+    /// retro_system_info::need_fullpath requires
+    /// that this path is valid.
+    pub path: *const libc::c_char,
+
+    /// Memory buffer of loaded game. Will be NULL
+    /// if need_fullpath was set.
+    pub data: *const libc::c_void,
+
+    /// Size of memory buffer.
+    pub size: libc::size_t,
+
+    /// String of implementation specific meta-data.
+    pub meta: *const libc::c_char,
+}
+
+impl GameInfo {
+    /// Game info for need_fullpath cores: only `path` is set.
+    pub fn from_path(path: &CStr) -> BorrowedGameInfo<'_> {
+        BorrowedGameInfo {
+            info: Self {
+                path: path.as_ptr(),
+                data: std::ptr::null(),
+                size: 0,
+                meta: std::ptr::null(),
+            },
+            _borrow: PhantomData,
+        }
+    }
+
+    /// Game info pointing at an in-memory buffer, without a path.
+    pub fn from_buffer(data: &[u8]) -> BorrowedGameInfo<'_> {
+        BorrowedGameInfo {
+            info: Self {
+                path: std::ptr::null(),
+                data: data.as_ptr().cast(),
+                size: data.len(),
+                meta: std::ptr::null(),
+            },
+            _borrow: PhantomData,
+        }
+    }
+}
+
+/// A [`GameInfo`] tied to the lifetime of the path, buffer and meta
+/// data it points at, so it cannot outlive them.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug)]
+pub struct BorrowedGameInfo<'a> {
+    info: GameInfo,
+    _borrow: PhantomData<&'a [u8]>,
+}
+
+impl<'a> BorrowedGameInfo<'a> {
+    /// Sets the content path, e.g. alongside an in-memory buffer.
+    pub fn with_path(mut self, path: &'a CStr) -> Self {
+        self.info.path = path.as_ptr();
+        self
+    }
+
+    /// Sets the implementation specific meta data.
+    pub fn with_meta(mut self, meta: &'a CStr) -> Self {
+        self.info.meta = meta.as_ptr();
+        self
+    }
+
+    /// Pointer suitable for retro_load_game().
+    pub fn as_ptr(&self) -> *const GameInfo {
+        &self.info
+    }
+}
+
+impl Deref for BorrowedGameInfo<'_> {
+    type Target = GameInfo;
+
+    fn deref(&self) -> &GameInfo {
+        &self.info
+    }
+}
+
+/// CRC32 of the content buffer in `info`, as used for database lookups.
+/// None when `data` is NULL, as for need_fullpath cores.
+///
+/// # Safety
+/// `info.data` must be NULL or point to `info.size` readable bytes.
+#[cfg(feature = "crc")]
+pub unsafe fn content_crc32(info: &GameInfo) -> Option<u32> {
+    if info.data.is_null() {
+        return None;
+    }
+    Some(crc32fast::hash(std::slice::from_raw_parts(info.data.cast(), info.size)))
+}
+
+/// Extended game info, see ENVIRONMENT_GET_GAME_INFO_EXT.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GameInfoExt {
+    /// - If file_in_archive is false, contains a valid
+    ///   path to an existent content file (UTF-8 encoded)
+    /// - If file_in_archive is true, may be NULL
+    pub full_path: *const libc::c_char,
+
+    /// - If file_in_archive is false, may be NULL
+    /// - If file_in_archive is true, contains a valid path
+    ///   to an existent compressed file inside which the
+    ///   content file is located (UTF-8 encoded)
+    pub archive_path: *const libc::c_char,
+
+    /// - If file_in_archive is false, may be NULL
+    /// - If file_in_archive is true, contain a valid path
+    ///   to an existent content file inside the compressed
+    ///   file referred to by archive_path (UTF-8 encoded)
+    pub archive_file: *const libc::c_char,
+
+    /// - If file_in_archive is false, contains a valid path
+    ///   to the directory in which the content file exists
+    ///   (UTF-8 encoded)
+    /// - If file_in_archive is true, contains a valid path
+    ///   to the directory in which the compressed file
+    ///   (containing the content file) exists (UTF-8 encoded)
+    pub dir: *const libc::c_char,
+
+    /// Contains the canonical name/ID of the content file
+    /// (UTF-8 encoded). Intended for use when identifying
+    /// 'complementary' content named after the loaded file -
+    /// i.e. companion data of a different format (a CD image
+    /// required by a ROM), texture packs, internally handled
+    /// save files, etc.
+    /// - If file_in_archive is false, contains the basename
+    ///   of the content file, without extension
+    /// - If file_in_archive is true, then string is
+    ///   implementation specific.
+    pub name: *const libc::c_char,
+
+    /// - If file_in_archive is false, contains the extension
+    ///   of the content file in lower case format
+    /// - If file_in_archive is true, contains the extension
+    ///   of the content file inside the compressed file,
+    ///   in lower case format
+    pub ext: *const libc::c_char,
+
+    /// String of implementation specific meta-data.
+    pub meta: *const libc::c_char,
+
+    /// Memory buffer of loaded game content. Will be NULL:
+    /// IF
+    /// - retro_system_info::need_fullpath is true and
+    ///   retro_system_content_info_override::need_fullpath
+    ///   is unset
+    /// OR
+    /// - retro_system_content_info_override::need_fullpath
+    ///   is true
+    pub data: *const libc::c_void,
+
+    /// Size of game content memory buffer, in bytes.
+    pub size: libc::size_t,
+
+    /// True if loaded content file is inside a compressed
+    /// archive.
+    pub file_in_archive: bool,
+
+    /// - If data is NULL, value is unset/ignored
+    /// - If data is non-NULL:
+    ///   - If persistent_data is false, data and size are
+    ///     valid only until retro_load_game() returns
+    ///   - If persistent_data is true, data and size are
+    ///     are valid until retro_deinit() returns
+    pub persistent_data: bool,
+}
+
+impl Default for GameInfoExt {
+    fn default() -> Self {
+        Self {
+            full_path: std::ptr::null(),
+            archive_path: std::ptr::null(),
+            archive_file: std::ptr::null(),
+            dir: std::ptr::null(),
+            name: std::ptr::null(),
+            ext: std::ptr::null(),
+            meta: std::ptr::null(),
+            data: std::ptr::null(),
+            size: 0,
+            file_in_archive: false,
+            persistent_data: false,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Variable {
+    /// Variable to query in RETRO_ENVIRONMENT_GET_VARIABLE.
+    /// If NULL, obtains the complete environment string if more
+    /// complex parsing is necessary.
+    /// The environment string is formatted as key-value pairs
+    /// delimited by semicolons as so:
+    /// "key1=value1;key2=value2;..."
+    pub key: *const libc::c_char,
+
+    /// Value to be obtained. If key does not exist, it is set to NULL.
+    pub value: *const libc::c_char,
+}
+
+impl Default for Variable {
+    /// The { NULL, NULL } terminator of a SET_VARIABLES array.
+    fn default() -> Self {
+        Self {
+            key: std::ptr::null(),
+            value: std::ptr::null(),
+        }
+    }
+}
+
+impl Variable {
+    /// Splits a SET_VARIABLES value into its description and
+    /// possible values, see [`parse_variable_value`].
+    ///
+    /// # Safety
+    /// `value` must be NULL or point to a valid C string that
+    /// outlives the returned strings.
+    pub unsafe fn parse_values(&self) -> Option<(Cow<'_, str>, Vec<Cow<'_, str>>)> {
+        if self.value.is_null() {
+            return None;
+        }
+        match CStr::from_ptr(self.value).to_string_lossy() {
+            Cow::Borrowed(value) => {
+                let (desc, values) = parse_variable_value(value)?;
+                Some((Cow::Borrowed(desc), values.into_iter().map(Cow::Borrowed).collect()))
+            }
+            Cow::Owned(value) => {
+                let (desc, values) = parse_variable_value(&value)?;
+                Some((
+                    Cow::Owned(desc.to_owned()),
+                    values.into_iter().map(|v| Cow::Owned(v.to_owned())).collect(),
+                ))
+            }
+        }
+    }
+}
+
+/// Splits `"Description; a|b|c"` into the description and the
+/// '|' delimited values, trimming whitespace around each part.
+/// Returns None when the "; " separator is missing or no values follow it.
+pub fn parse_variable_value(value: &str) -> Option<(&str, Vec<&str>)> {
+    let (desc, values) = value.split_once("; ")?;
+    let values: Vec<&str> = values.split('|').map(str::trim).filter(|v| !v.is_empty()).collect();
+    if values.is_empty() {
+        return None;
+    }
+    Some((desc.trim(), values))
+}
+
+/// Maximum number of values permitted for a core option.
+/// > Note: We have to set a maximum value due the limitations
+///   of the C language - i.e. it is not possible to create an
+///   array of structs each containing a variable sized array,
+///   so the retro_core_option_definition values array must
+///   have a fixed size. The size limit of 128 is a balancing
+///   act - it needs to be large enough to support all 'sane'
+///   core options, but setting it too large may impact low memory
+///   platforms. In practise, if a core option has more than
+///   128 values then the implementation is likely flawed.
+///   To quote the above API reference:
+///      "The number of possible options should be very limited
+///       i.e. it should be feasible to cycle through options
+///       without a keyboard."
+pub const NUM_CORE_OPTION_VALUES_MAX: usize = 128;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CoreOptionValue {
+    /// Expected option value
+    pub value: *const libc::c_char,
+
+    /// Human-readable value label. If NULL, value itself
+    /// will be displayed by the frontend
+    pub label: *const libc::c_char,
+}
+
+impl Default for CoreOptionValue {
+    fn default() -> Self {
+        Self {
+            value: std::ptr::null(),
+            label: std::ptr::null(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CoreOptionDefinition {
+    /// Variable to query in RETRO_ENVIRONMENT_GET_VARIABLE.
+    pub key: *const libc::c_char,
+
+    /// Human-readable core option description (used as menu label)
+    pub desc: *const libc::c_char,
+
+    /// Human-readable core option information (used as menu sublabel)
+    pub info: *const libc::c_char,
+
+    /// Array of retro_core_option_value structs, terminated by NULL
+    pub values: [CoreOptionValue; NUM_CORE_OPTION_VALUES_MAX],
+
+    /// Default core option value. Must match one of the values
+    /// in the retro_core_option_value array, otherwise will be
+    /// ignored
+    pub default_value: *const libc::c_char,
+}
+
+impl Default for CoreOptionDefinition {
+    /// All NULL; doubles as the terminating entry of a definitions array.
+    fn default() -> Self {
+        Self {
+            key: std::ptr::null(),
+            desc: std::ptr::null(),
+            info: std::ptr::null(),
+            values: [CoreOptionValue::default(); NUM_CORE_OPTION_VALUES_MAX],
+            default_value: std::ptr::null(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CoreOptionV2Category {
+    /// Variable uniquely identifying the
+    /// option category. Valid key characters
+    /// are [a-z, A-Z, 0-9, _, -]
+    pub key: *const libc::c_char,
+
+    /// Human-readable category description
+    /// > Used as category menu label when
+    ///   frontend has core option category
+    ///   support
+    pub desc: *const libc::c_char,
+
+    /// Human-readable category information
+    /// > Used as category menu sublabel when
+    ///   frontend has core option category
+    ///   support
+    /// > Optional (may be NULL or an empty
+    ///   string)
+    pub info: *const libc::c_char,
+}
+
+impl Default for CoreOptionV2Category {
+    /// All NULL; doubles as the terminating entry of a categories array.
+    fn default() -> Self {
+        Self {
+            key: std::ptr::null(),
+            desc: std::ptr::null(),
+            info: std::ptr::null(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CoreOptionV2Definition {
+    /// Variable to query in RETRO_ENVIRONMENT_GET_VARIABLE.
+    /// Valid key characters are [a-z, A-Z, 0-9, _, -]
+    pub key: *const libc::c_char,
+
+    /// Human-readable core option description
+    /// > Used as menu label when frontend does
+    ///   not have core option category support
+    ///   e.g. "Video > Aspect Ratio"
+    pub desc: *const libc::c_char,
+
+    /// Human-readable core option description
+    /// > Used as menu label when frontend has
+    ///   core option category support
+    ///   e.g. "Aspect Ratio", where associated
+    ///   retro_core_option_v2_category::desc
+    ///   is "Video"
+    /// > If empty or NULL, the string specified by
+    ///   desc will be used as the menu label
+    /// > Will be ignored (and may be set to NULL)
+    ///   if category_key is empty or NULL
+    pub desc_categorized: *const libc::c_char,
+
+    /// Human-readable core option information
+    /// > Used as menu sublabel
+    pub info: *const libc::c_char,
+
+    /// Human-readable core option information
+    /// > Used as menu sublabel when frontend
+    ///   has core option category support
+    ///   (e.g. may be required when info text
+    ///   references an option by name/desc,
+    ///   and the desc/desc_categorized text
+    ///   for that option differ)
+    /// > If empty or NULL, the string specified by
+    ///   info will be used as the menu sublabel
+    /// > Will be ignored (and may be set to NULL)
+    ///   if category_key is empty or NULL
+    pub info_categorized: *const libc::c_char,
+
+    /// Variable specifying category (e.g. "video",
+    /// "audio") that will be assigned to the option
+    /// if frontend has core option category support.
+    /// > Categorized options will be displayed in a
+    ///   subsection/submenu of the frontend core
+    ///   option interface
+    /// > Specified string must match one of the
+    ///   retro_core_option_v2_category::key values
+    ///   in the associated retro_core_option_v2_category
+    ///   array; If no match is not found, specified
+    ///   string will be considered as NULL
+    /// > If specified string is empty or NULL, option will
+    ///   have no category and will be shown at the top
+    ///   level of the frontend core option interface
+    pub category_key: *const libc::c_char,
+
+    /// Array of retro_core_option_value structs, terminated by NULL
+    pub values: [CoreOptionValue; NUM_CORE_OPTION_VALUES_MAX],
+
+    /// Default core option value. Must match one of the values
+    /// in the retro_core_option_value array, otherwise will be
+    /// ignored
+    pub default_value: *const libc::c_char,
+}
+
+impl Default for CoreOptionV2Definition {
+    /// All NULL; doubles as the terminating entry of a definitions array.
+    fn default() -> Self {
+        Self {
+            key: std::ptr::null(),
+            desc: std::ptr::null(),
+            desc_categorized: std::ptr::null(),
+            info: std::ptr::null(),
+            info_categorized: std::ptr::null(),
+            category_key: std::ptr::null(),
+            values: [CoreOptionValue::default(); NUM_CORE_OPTION_VALUES_MAX],
+            default_value: std::ptr::null(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CoreOptionsV2 {
+    /// Array of retro_core_option_v2_category structs,
+    /// terminated by NULL
+    /// > If NULL, all entries in definitions array
+    ///   will have no category and will be shown at
+    ///   the top level of the frontend core option
+    ///   interface
+    /// > Will be ignored if frontend does not have
+    ///   core option category support
+    pub categories: *mut CoreOptionV2Category,
+
+    /// Array of retro_core_option_v2_definition structs,
+    /// terminated by NULL
+    pub definitions: *mut CoreOptionV2Definition,
+}
+
+/// Owns the strings and definitions of a category-less CoreOptionsV2
+/// table built at runtime. Interior NUL bytes in strings are dropped.
+#[derive(Debug)]
+pub struct CoreOptionsV2Builder {
+    strings: Vec<CString>,
+    // Always ends with the NULL terminator.
+    definitions: Vec<CoreOptionV2Definition>,
+}
+
+impl Default for CoreOptionsV2Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CoreOptionsV2Builder {
+    pub fn new() -> Self {
+        Self {
+            strings: Vec::new(),
+            definitions: vec![CoreOptionV2Definition::default()],
+        }
+    }
+
+    fn intern(&mut self, s: &str) -> *const libc::c_char {
+        let s = CString::new(s.replace('\0', "")).expect("interior NUL bytes are filtered out");
+        // The CString's buffer does not move when `strings` reallocates.
+        let ptr = s.as_ptr();
+        self.strings.push(s);
+        ptr
+    }
+
+    /// Adds an option. Values beyond NUM_CORE_OPTION_VALUES_MAX - 1 are
+    /// dropped so the values array stays NULL terminated.
+    pub fn option(&mut self, key: &str, desc: &str, values: &[&str], default: &str) -> &mut Self {
+        let mut def = CoreOptionV2Definition {
+            key: self.intern(key),
+            desc: self.intern(desc),
+            default_value: self.intern(default),
+            ..Default::default()
+        };
+        for (slot, value) in def.values[..NUM_CORE_OPTION_VALUES_MAX - 1].iter_mut().zip(values) {
+            slot.value = self.intern(value);
+        }
+        let terminator = self.definitions.len() - 1;
+        self.definitions.insert(terminator, def);
+        self
+    }
+
+    /// The options added so far, without the terminator.
+    pub fn definitions(&self) -> &[CoreOptionV2Definition] {
+        &self.definitions[..self.definitions.len() - 1]
+    }
+
+    /// Table for SET_CORE_OPTIONS_V2, pointing into the builder. It is
+    /// only valid until the builder is modified or dropped.
+    pub fn as_options_v2(&mut self) -> CoreOptionsV2 {
+        CoreOptionsV2 {
+            categories: std::ptr::null_mut(),
+            definitions: self.definitions.as_mut_ptr(),
+        }
+    }
+}
+
+/// Converts a SET_VARIABLES array to v2 definitions, for cores moving to
+/// SET_CORE_OPTIONS_V2 or frontends presenting both the same way. The
+/// first value of each variable becomes its default, as in v1. Variables
+/// whose value does not parse with [`parse_variable_value`] are skipped.
+///
+/// # Safety
+/// `vars` may end with a NULL-key terminator, after which entries are
+/// ignored; every other key and value must be NULL or a valid C string.
+pub unsafe fn variables_to_options_v2(vars: &[Variable]) -> CoreOptionsV2Builder {
+    let mut builder = CoreOptionsV2Builder::new();
+    for var in vars.iter().take_while(|var| !var.key.is_null()) {
+        let Some((desc, values)) = var.parse_values() else {
+            continue;
+        };
+        let values: Vec<&str> = values.iter().map(|value| value.as_ref()).collect();
+        builder.option(&CStr::from_ptr(var.key).to_string_lossy(), &desc, &values, values[0]);
+    }
+    builder
+}
+
+/// Slice over a C array ending at the first entry for which `is_end`
+/// holds, excluding that entry. A NULL array is empty.
+///
+/// # Safety
+/// `ptr` must be NULL or point to such a terminated array that stays
+/// valid and unmodified for `'a`.
+unsafe fn terminated_slice<'a, T>(ptr: *const T, is_end: impl Fn(&T) -> bool) -> &'a [T] {
+    if ptr.is_null() {
+        return &[];
+    }
+    let mut len = 0;
+    while !is_end(&*ptr.add(len)) {
+        len += 1;
+    }
+    std::slice::from_raw_parts(ptr, len)
+}
+
+/// The option categories in declaration order, which cores use as the
+/// intended display order.
+///
+/// # Safety
+/// `opts.categories` must be NULL or point to a category array
+/// terminated by an entry with a NULL key, valid for the borrow of `opts`.
+pub unsafe fn ordered_categories(opts: &CoreOptionsV2) -> Vec<&CoreOptionV2Category> {
+    terminated_slice(opts.categories, |category| category.key.is_null())
+        .iter()
+        .collect()
+}
+
+/// Submits `opts` with SET_CORE_OPTIONS_V2, returning
+/// `(submitted_ok, categories_supported)`. Nothing is sent, and both are
+/// false, when the frontend reports a core options version below 2; the
+/// core should then fall back to an older interface. Categories only
+/// count as supported when the frontend says so and `opts` declares any.
+///
+/// # Safety
+/// `cb` must be NULL or the environment callback given to the core, and
+/// `opts` must satisfy the requirements of SET_CORE_OPTIONS_V2.
+pub unsafe fn submit_and_check_categories(cb: retro_environment_t, opts: &CoreOptionsV2) -> (bool, bool) {
+    let Some(cb) = cb else {
+        return (false, false);
+    };
+    let mut version: libc::c_uint = 0;
+    if !cb(ENVIRONMENT_GET_CORE_OPTIONS_VERSION, (&mut version as *mut libc::c_uint).cast()) || version < 2 {
+        return (false, false);
+    }
+    let categories = cb(ENVIRONMENT_SET_CORE_OPTIONS_V2, (opts as *const CoreOptionsV2).cast_mut().cast());
+    (true, categories && !ordered_categories(opts).is_empty())
+}
+
+/// Passed to ENVIRONMENT_SET_CORE_OPTIONS_INTL. `local` stays NULL when
+/// there are no translations for the frontend language:
+///
+/// ```
+/// use libretro_sys::{CoreOptionDefinition, CoreOptionsIntl};
+///
+/// let mut us = [CoreOptionDefinition::default(); 2];
+/// us[0].key = c"mycore_frameskip".as_ptr();
+/// us[0].desc = c"Frameskip".as_ptr();
+/// us[0].values[0].value = c"disabled".as_ptr();
+/// us[0].values[1].value = c"auto".as_ptr();
+/// us[0].default_value = c"disabled".as_ptr();
+///
+/// let intl = CoreOptionsIntl {
+///     us: us.as_mut_ptr(),
+///     local: std::ptr::null_mut(),
+/// };
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CoreOptionsIntl {
+    /// Pointer to an array of retro_core_option_definition structs
+    /// - US English implementation
+    /// - Must point to a valid array
+    pub us: *mut CoreOptionDefinition,
+
+    /// Pointer to an array of retro_core_option_definition structs
+    /// - Implementation for current frontend language
+    /// - May be NULL
+    pub local: *mut CoreOptionDefinition,
+}
+
+impl Default for CoreOptionsIntl {
+    fn default() -> Self {
+        Self {
+            us: std::ptr::null_mut(),
+            local: std::ptr::null_mut(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CoreOptionsV2Intl {
+    /// Pointer to a retro_core_options_v2 struct
+    /// > US English implementation
+    /// > Must point to a valid struct
+    pub us: *mut CoreOptionsV2,
+
+    /// Pointer to a retro_core_options_v2 struct
+    /// - Implementation for current frontend language
+    /// - May be NULL
+    pub local: *mut CoreOptionsV2,
+}
+
+impl Default for CoreOptionsV2Intl {
+    fn default() -> Self {
+        Self {
+            us: std::ptr::null_mut(),
+            local: std::ptr::null_mut(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CoreOptionDisplay {
+    /// Variable to configure in RETRO_ENVIRONMENT_SET_CORE_OPTIONS_DISPLAY
+    pub key: *const libc::c_char,
+
+    /// Specifies whether variable should be displayed
+    /// when presenting core options to the user
+    pub visible: bool,
+}
+
+/// Core-side record of which options are shown, applied with
+/// SET_CORE_OPTIONS_DISPLAY. The frontend makes every option visible
+/// again whenever options are submitted, so cores flush this after each
+/// submission to keep hidden options hidden.
+#[derive(Clone, Debug, Default)]
+pub struct VisibilityState {
+    visible: BTreeMap<CString, bool>,
+}
+
+impl VisibilityState {
+    /// Tracks the options declared by `opts`, all visible.
+    ///
+    /// # Safety
+    /// Same requirements as [`VisibilityState::set_options`].
+    pub unsafe fn from_options_v2(opts: &CoreOptionsV2) -> Self {
+        let mut state = Self::default();
+        state.set_options(opts);
+        state
+    }
+
+    /// Switches to the options declared by `opts`, as when the core
+    /// submits a new table. Options that are still declared keep their
+    /// visibility, new ones start visible and the rest are forgotten.
+    ///
+    /// # Safety
+    /// `opts.definitions` must be NULL or point to a definitions array
+    /// terminated by an entry with a NULL key, whose keys are valid C strings.
+    pub unsafe fn set_options(&mut self, opts: &CoreOptionsV2) {
+        let mut visible = BTreeMap::new();
+        for def in terminated_slice(opts.definitions, |def| def.key.is_null()) {
+            let key = CStr::from_ptr(def.key).to_owned();
+            let shown = self.visible.get(&key).copied().unwrap_or(true);
+            visible.insert(key, shown);
+        }
+        self.visible = visible;
+    }
+
+    /// Shows or hides `key`. Returns false for unknown keys.
+    pub fn set_visible(&mut self, key: &CStr, visible: bool) -> bool {
+        match self.visible.get_mut(key) {
+            Some(shown) => {
+                *shown = visible;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// None for unknown keys.
+    pub fn is_visible(&self, key: &CStr) -> Option<bool> {
+        self.visible.get(key).copied()
+    }
+
+    /// Sends the visibility of every tracked option to the frontend.
+    /// Returns true if the frontend accepted all of them, and false
+    /// without calling anything when `cb` is NULL.
+    ///
+    /// # Safety
+    /// `cb` must be NULL or the environment callback given to the core.
+    pub unsafe fn flush(&self, cb: retro_environment_t) -> bool {
+        let Some(cb) = cb else {
+            return false;
+        };
+        let mut accepted = true;
+        for (key, &visible) in &self.visible {
+            let mut display = CoreOptionDisplay { key: key.as_ptr(), visible };
+            accepted &= cb(ENVIRONMENT_SET_CORE_OPTIONS_DISPLAY, (&mut display as *mut CoreOptionDisplay).cast());
+        }
+        accepted
+    }
+}
+
+/// Gets called from the frontend when core option visibility may have
+/// changed; returns true if any visibility was updated.
+pub type retro_core_options_update_display_callback_t = Option<unsafe extern "C" fn() -> bool>;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CoreOptionsUpdateDisplayCallback {
+    pub callback: retro_core_options_update_display_callback_t,
+}
+
+/// Snapshot of the KEYBOARD device for the RETROK_* codes a core cares
+/// about, read with a single pass through a retro_input_state_t style
+/// callback `(port, device, index, id) -> i16`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyboardState {
+    down: BTreeSet<libc::c_uint>,
+}
+
+impl KeyboardState {
+    pub fn read<F>(port: libc::c_uint, keys: &[libc::c_uint], mut input_state: F) -> Self
+    where
+        F: FnMut(libc::c_uint, libc::c_uint, libc::c_uint, libc::c_uint) -> i16,
+    {
+        let down = keys
+            .iter()
+            .copied()
+            .filter(|&key| input_state(port, DEVICE_KEYBOARD, 0, key) != 0)
+            .collect();
+        Self { down }
+    }
+
+    /// False for keys that were not part of the read.
+    pub fn is_down(&self, key: libc::c_uint) -> bool {
+        self.down.contains(&key)
+    }
+}
+
+/// Pairs the input poll and input state callbacks so cores cannot read
+/// input without calling input_poll at least once per retro_run(): reads
+/// before the frame's first poll return None.
+pub struct InputPoller<P, S> {
+    poll: P,
+    state: S,
+    polled: bool,
+}
+
+impl<P, S> InputPoller<P, S>
+where
+    P: FnMut(),
+    S: FnMut(libc::c_uint, libc::c_uint, libc::c_uint, libc::c_uint) -> i16,
+{
+    pub fn new(poll: P, state: S) -> Self {
+        Self {
+            poll,
+            state,
+            polled: false,
+        }
+    }
+
+    /// Must be called at the start of every retro_run().
+    pub fn begin_frame(&mut self) {
+        self.polled = false;
+    }
+
+    pub fn poll(&mut self) {
+        (self.poll)();
+        self.polled = true;
+    }
+
+    pub fn polled(&self) -> bool {
+        self.polled
+    }
+
+    /// None if input has not been polled this frame.
+    pub fn state(
+        &mut self,
+        port: libc::c_uint,
+        device: libc::c_uint,
+        index: libc::c_uint,
+        id: libc::c_uint,
+    ) -> Option<i16> {
+        self.polled.then(|| (self.state)(port, device, index, id))
+    }
+
+    /// None if input has not been polled this frame.
+    pub fn lightgun(&mut self, port: libc::c_uint) -> Option<LightgunState> {
+        self.polled.then(|| LightgunState::read(port, &mut self.state))
+    }
+
+    /// None if input has not been polled this frame.
+    pub fn mouse(&mut self, port: libc::c_uint) -> Option<MouseState> {
+        self.polled.then(|| MouseState::read(port, &mut self.state))
+    }
+
+    /// None if input has not been polled this frame.
+    pub fn keyboard(&mut self, port: libc::c_uint, keys: &[libc::c_uint]) -> Option<KeyboardState> {
+        self.polled.then(|| KeyboardState::read(port, keys, &mut self.state))
+    }
+}
+
+/// Environment callback. Gives implementations a way of performing
+/// uncommon tasks. Extensible.
+///
+/// Kept from retro_set_environment() for later calls:
+///
+/// ```
+/// use std::sync::Mutex;
+/// use libretro_sys::{retro_environment_t, ENVIRONMENT_GET_CAN_DUPE};
+///
+/// static ENVIRONMENT: Mutex<retro_environment_t> = Mutex::new(None);
+///
+/// #[no_mangle]
+/// pub extern "C" fn retro_set_environment(cb: retro_environment_t) {
+///     *ENVIRONMENT.lock().unwrap() = cb;
+/// }
+///
+/// fn can_dupe() -> bool {
+///     let Some(environment) = *ENVIRONMENT.lock().unwrap() else {
+///         return false;
+///     };
+///     let mut can_dupe = false;
+///     unsafe { environment(ENVIRONMENT_GET_CAN_DUPE, (&mut can_dupe as *mut bool).cast()) && can_dupe }
+/// }
+/// ```
+pub type retro_environment_t = Option<unsafe extern "C" fn(cmd: libc::c_uint, data: *mut libc::c_void) -> bool>;
+
+/// Render a frame. Pixel format is 15-bit 0RGB1555 native endian
+/// unless changed (see RETRO_ENVIRONMENT_SET_PIXEL_FORMAT).
+///
+/// Width and height specify dimensions of buffer.
+/// Pitch specifices length in bytes between two lines in buffer.
+///
+/// For performance reasons, it is highly recommended to have a frame
+/// that is packed in memory, i.e. pitch == width * byte_per_pixel.
+/// Certain graphic APIs, such as OpenGL ES, do not like textures
+/// that are not packed in memory.
+///
+/// Kept from retro_set_video_refresh(), like the other driver callbacks:
+///
+/// ```
+/// use std::sync::Mutex;
+/// use libretro_sys::retro_video_refresh_t;
+///
+/// static VIDEO_REFRESH: Mutex<retro_video_refresh_t> = Mutex::new(None);
+///
+/// #[no_mangle]
+/// pub extern "C" fn retro_set_video_refresh(cb: retro_video_refresh_t) {
+///     *VIDEO_REFRESH.lock().unwrap() = cb;
+/// }
+/// ```
+pub type retro_video_refresh_t =
+    Option<unsafe extern "C" fn(data: *const libc::c_void, width: libc::c_uint, height: libc::c_uint, pitch: libc::size_t)>;
+
+/// Renders a single audio frame. Should only be used if implementation
+/// generates a single sample at a time.
+/// Format is signed 16-bit native endian.
+pub type retro_audio_sample_t = Option<unsafe extern "C" fn(left: i16, right: i16)>;
+
+/// Renders multiple audio frames in one go.
+///
+/// One frame is defined as a sample of left and right channels, interleaved.
+/// I.e. int16_t buf[4] = { l, r, l, r }; would be 2 frames.
+/// Only one of the audio callbacks must ever be used.
+pub type retro_audio_sample_batch_t = Option<unsafe extern "C" fn(data: *const i16, frames: libc::size_t) -> libc::size_t>;
+
+/// Polls input.
+pub type retro_input_poll_t = Option<unsafe extern "C" fn()>;
+
+/// Queries for input for player 'port'. device will be masked with
+/// RETRO_DEVICE_MASK.
+///
+/// Specialization of devices such as RETRO_DEVICE_JOYPAD_MULTITAP that
+/// have been set with retro_set_controller_port_device()
+/// will still use the higher level RETRO_DEVICE_JOYPAD to request input.
+pub type retro_input_state_t =
+    Option<unsafe extern "C" fn(port: libc::c_uint, device: libc::c_uint, index: libc::c_uint, id: libc::c_uint) -> i16>;
+
+/// NULL and empty strings both read as None.
+///
+/// # Safety
+/// `ptr` must be NULL or point to a valid C string that lives for `'a`.
+unsafe fn optional_cstr<'a>(ptr: *const libc::c_char) -> Option<&'a CStr> {
+    if ptr.is_null() {
+        return None;
+    }
+    let s = CStr::from_ptr(ptr);
+    if s.is_empty() {
+        None
+    } else {
+        Some(s)
+    }
+}
+
+impl CoreOptionDefinition {
+    /// Sublabel text, None when `info` is NULL or empty.
+    ///
+    /// # Safety
+    /// `info` must be NULL or point to a valid C string.
+    pub unsafe fn info(&self) -> Option<&CStr> {
+        optional_cstr(self.info)
+    }
+}
+
+impl CoreOptionV2Category {
+    /// Sublabel text, None when `info` is NULL or empty.
+    ///
+    /// # Safety
+    /// `info` must be NULL or point to a valid C string.
+    pub unsafe fn info(&self) -> Option<&CStr> {
+        optional_cstr(self.info)
+    }
+}
+
+impl CoreOptionV2Definition {
+    /// Sublabel text, None when `info` is NULL or empty.
+    ///
+    /// # Safety
+    /// `info` must be NULL or point to a valid C string.
+    pub unsafe fn info(&self) -> Option<&CStr> {
+        optional_cstr(self.info)
+    }
+
+    /// Sublabel text for frontends with category support, falling back
+    /// to `info` when `info_categorized` is NULL or empty.
+    ///
+    /// # Safety
+    /// `info` and `info_categorized` must each be NULL or point to a
+    /// valid C string.
+    pub unsafe fn info_categorized(&self) -> Option<&CStr> {
+        optional_cstr(self.info_categorized).or_else(|| self.info())
+    }
+}
+
+static TEMP_PATH_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Unique path for temporary data inside `save_dir`, built from `prefix`,
+/// the process id and a per-process counter, e.g. "saves/state-1234-0.tmp".
+/// An empty `save_dir` gives a path relative to the working directory.
+/// The file itself is not created.
+pub fn temp_path(save_dir: &CStr, prefix: &str) -> CString {
+    let dir = save_dir.to_string_lossy();
+    let dir = dir.trim_end_matches(['/', '\\']);
+    let count = TEMP_PATH_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let name = format!("{}-{}-{}.tmp", prefix.replace('\0', ""), std::process::id(), count);
+    let path = if save_dir.is_empty() { name } else { format!("{}/{}", dir, name) };
+    CString::new(path).expect("interior NUL bytes are filtered out")
+}
+
+/// All pointers are owned by libretro implementation, and pointers must
+/// remain valid until it is unloaded.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SystemInfo {
+    /// Descriptive name of library. Should not
+    /// contain any version numbers, etc.
+    pub library_name: *const libc::c_char,
+
+    /// Descriptive version of core.
+    pub library_version: *const libc::c_char,
+
+    /// A string listing probably content extensions the core will be able to
+    /// load, separated with pipe. I.e. "bin|rom|iso".
+    /// Typically used for a GUI to filter out extensions.
+    pub valid_extensions: *const libc::c_char,
+
+    /// Libretro cores that need to have direct access to their content
+    /// files, including cores which use the path of the content files to
+    /// determine the paths of other files, should set need_fullpath to true.
+    ///
+    /// Cores should strive for setting need_fullpath to false,
+    /// as it allows the frontend to perform patching, etc.
+    ///
+    /// If need_fullpath is true and retro_load_game() is called:
+    ///    - retro_game_info::path is guaranteed to have a valid path
+    ///    - retro_game_info::data and retro_game_info::size are invalid
+    ///
+    /// If need_fullpath is false and retro_load_game() is called:
+    ///    - retro_game_info::path may be NULL
+    ///    - retro_game_info::data and retro_game_info::size are guaranteed
+    ///      to be valid
+    pub need_fullpath: bool,
+
+    /// If true, the frontend is not allowed to extract any archives before
+    /// loading the real content.
+    /// Necessary for certain libretro implementations that load games
+    /// from zipped archives.
+    pub block_extract: bool,
+}
+
+impl Default for SystemInfo {
+    fn default() -> Self {
+        Self {
+            library_name: std::ptr::null(),
+            library_version: std::ptr::null(),
+            valid_extensions: std::ptr::null(),
+            need_fullpath: false,
+            block_extract: false,
+        }
+    }
+}
+
+impl SystemInfo {
+    /// The extensions in valid_extensions, cleaned up the same way as
+    /// [`normalize_extensions`] so frontends get a usable list even from
+    /// cores that format the string wrongly.
+    ///
+    /// # Safety
+    /// `valid_extensions` must be NULL or point to a NUL-terminated string.
+    pub unsafe fn extensions(&self) -> Vec<String> {
+        let Some(exts) = optional_cstr(self.valid_extensions) else {
+            return Vec::new();
+        };
+        let exts = exts.to_string_lossy();
+        let normalized = normalize_extensions(&exts.split('|').collect::<Vec<_>>());
+        normalized.to_str().unwrap_or_default().split('|').filter(|ext| !ext.is_empty()).map(String::from).collect()
+    }
+}
+
+// The libretro API entry points, for frontends linking a core statically.
+// Frontends that load cores at runtime resolve these symbols instead.
+#[cfg(feature = "link-core")]
+extern "C" {
+    /// Sets callbacks. retro_set_environment() is guaranteed to be called
+    /// before retro_init().
+    ///
+    /// The rest of the set_* functions are guaranteed to have been called
+    /// before the first call to retro_run() is made.
+    pub fn retro_set_environment(cb: retro_environment_t);
+    pub fn retro_set_video_refresh(cb: retro_video_refresh_t);
+    pub fn retro_set_audio_sample(cb: retro_audio_sample_t);
+    pub fn retro_set_audio_sample_batch(cb: retro_audio_sample_batch_t);
+    pub fn retro_set_input_poll(cb: retro_input_poll_t);
+    pub fn retro_set_input_state(cb: retro_input_state_t);
+
+    /// Library global initialization/deinitialization.
+    pub fn retro_init();
+    pub fn retro_deinit();
+
+    /// Must return RETRO_API_VERSION. Used to validate ABI compatibility
+    /// when the API is revised.
+    pub fn retro_api_version() -> libc::c_uint;
+
+    /// Gets statically known system info. Pointers provided in *info
+    /// must be statically allocated.
+    /// Can be called at any time, even before retro_init().
+    pub fn retro_get_system_info(info: *mut SystemInfo);
+
+    /// Gets information about system audio/video timings and geometry.
+    /// Can be called only after retro_load_game() has successfully completed.
+    /// NOTE: The implementation of this function might not initialize every
+    /// variable if needed.
+    /// E.g. geom.aspect_ratio might not be initialized if core doesn't
+    /// desire a particular aspect ratio.
+    pub fn retro_get_system_av_info(info: *mut SystemAvInfo);
+
+    /// Sets device to be used for player 'port'.
+    /// By default, RETRO_DEVICE_JOYPAD is assumed to be plugged into all
+    /// available ports.
+    /// Setting a particular device type is not a guarantee that libretro cores
+    /// will only poll input based on that particular device type. It is only a
+    /// hint to the libretro core when a core cannot automatically detect the
+    /// appropriate input device type on its own. It is also relevant when a
+    /// core can change its behavior depending on device type.
+    ///
+    /// As part of the core's implementation of retro_set_controller_port_device,
+    /// the core should call RETRO_ENVIRONMENT_SET_INPUT_DESCRIPTORS to notify the
+    /// frontend if the descriptions for any controls have changed as a
+    /// result of changing the device type.
+    pub fn retro_set_controller_port_device(port: libc::c_uint, device: libc::c_uint);
+
+    /// Resets the current game.
+    pub fn retro_reset();
+
+    /// Runs the game for one video frame.
+    /// During retro_run(), input_poll callback must be called at least once.
+    ///
+    /// If a frame is not rendered for reasons where a game "dropped" a frame,
+    /// this still counts as a frame, and retro_run() should explicitly dupe
+    /// a frame if GET_CAN_DUPE returns true.
+    /// In this case, the video callback can take a NULL argument for data.
+    pub fn retro_run();
+
+    /// Returns the amount of data the implementation requires to serialize
+    /// internal state (save states).
+    /// Between calls to retro_load_game() and retro_unload_game(), the
+    /// returned size is never allowed to be larger than a previous returned
+    /// value, to ensure that the frontend can allocate a save state buffer once.
+    pub fn retro_serialize_size() -> libc::size_t;
+
+    /// Serializes internal state. If failed, or size is lower than
+    /// retro_serialize_size(), it should return false, true otherwise.
+    pub fn retro_serialize(data: *mut libc::c_void, size: libc::size_t) -> bool;
+    pub fn retro_unserialize(data: *const libc::c_void, size: libc::size_t) -> bool;
+
+    pub fn retro_cheat_reset();
+    pub fn retro_cheat_set(index: libc::c_uint, enabled: bool, code: *const libc::c_char);
+
+    /// Loads a game.
+    /// Return true to indicate successful loading and false to indicate load failure.
+    pub fn retro_load_game(game: *const GameInfo) -> bool;
+
+    /// Loads a "special" kind of game. Should not be used,
+    /// except in extreme cases.
+    pub fn retro_load_game_special(game_type: libc::c_uint, info: *const GameInfo, num_info: libc::size_t) -> bool;
+
+    /// Unloads the currently loaded game. Called before retro_deinit(void).
+    pub fn retro_unload_game();
+
+    /// Gets region of game.
+    pub fn retro_get_region() -> libc::c_uint;
+
+    /// Gets region of memory.
+    pub fn retro_get_memory_data(id: libc::c_uint) -> *mut libc::c_void;
+    pub fn retro_get_memory_size(id: libc::c_uint) -> libc::size_t;
+}
+
+/// Callback type passed in RETRO_ENVIRONMENT_SET_KEYBOARD_CALLBACK.
+/// Called by the frontend in response to keyboard events.
+/// down is set if the key is being pressed, or false if it is being released.
+/// keycode is the RETROK value of the char, see [`Key::from_code`].
+/// character is the text character of the pressed key. (UTF-32).
+/// key_modifiers is a set of RETROKMOD values or'ed together, see [`KeyMod`].
+///
+/// The pressed/keycode state can be indepedent of the character.
+/// It is also possible that multiple characters are generated from a
+/// single keypress.
+/// Keycode events should be treated separately from character events.
+/// However, when possible, the frontend should try to synchronize these.
+/// If only a character is posted, keycode should be RETROK_UNKNOWN.
+///
+/// Similarily if only a keycode event is generated with no corresponding
+/// character, character should be 0.
+pub type retro_keyboard_event_t =
+    Option<unsafe extern "C" fn(down: bool, keycode: libc::c_uint, character: u32, key_modifiers: u16)>;
+
+/// Passed to ENVIRONMENT_SET_KEYBOARD_CALLBACK:
+///
+/// ```
+/// use std::ffi::c_uint;
+/// use libretro_sys::{Key, KeyMod, KeyboardCallback};
+///
+/// unsafe extern "C" fn keyboard_event(down: bool, keycode: c_uint, _character: u32, key_modifiers: u16) {
+///     let ctrl = KeyMod(key_modifiers).contains(KeyMod::CTRL);
+///     if let (true, Some(Key::R)) = (down && ctrl, Key::from_code(keycode)) {
+///         // Ctrl+R: reset the emulated machine.
+///     }
+/// }
+///
+/// let callback = KeyboardCallback {
+///     callback: Some(keyboard_event),
+/// };
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeyboardCallback {
+    pub callback: retro_keyboard_event_t,
+}
+
+/// If ejected is true, "ejects" the virtual disk tray.
+/// When ejected, the disk image index can be set.
+pub type retro_set_eject_state_t = Option<unsafe extern "C" fn(ejected: bool) -> bool>;
+
+/// Gets current eject state. The initial state is 'not ejected'.
+pub type retro_get_eject_state_t = Option<unsafe extern "C" fn() -> bool>;
+
+/// Gets current disk index. First disk is index 0.
+/// If return value is >= get_num_images(), no disk is currently inserted.
+pub type retro_get_image_index_t = Option<unsafe extern "C" fn() -> libc::c_uint>;
+
+/// Sets image index. Can only be called when disk is ejected.
+/// The implementation supports setting "no disk" by using an
+/// index >= get_num_images().
+pub type retro_set_image_index_t = Option<unsafe extern "C" fn(index: libc::c_uint) -> bool>;
+
+/// Gets total number of images which are available to use.
+pub type retro_get_num_images_t = Option<unsafe extern "C" fn() -> libc::c_uint>;
+
+/// Replaces the disk image associated with index.
+/// Arguments to pass in info have same requirements as retro_load_game().
+/// Virtual disk tray must be ejected when calling this.
+///
+/// Replacing a disk image with info = NULL will remove the disk image
+/// from the internal list.
+/// As a result, calls to get_image_index() can change.
+///
+/// E.g. replace_image_index(1, NULL), and previous get_image_index()
+/// returned 4 before.
+/// Index 1 will be removed, and the new index is 3.
+pub type retro_replace_image_index_t = Option<unsafe extern "C" fn(index: libc::c_uint, info: *const GameInfo) -> bool>;
+
+/// Adds a new valid index (get_num_images()) to the internal disk list.
+/// This will increment subsequent return values from get_num_images() by 1.
+/// This image index cannot be used until a disk image has been set
+/// with replace_image_index.
+pub type retro_add_image_index_t = Option<unsafe extern "C" fn() -> bool>;
+
+/// Callbacks for RETRO_ENVIRONMENT_SET_DISK_CONTROL_INTERFACE.
+/// Should be set for implementations which can swap out multiple disk
+/// images in runtime.
+///
+/// If the implementation can do this automatically, it should strive to do so.
+/// However, there are cases where the user must manually do so.
+///
+/// Overview: To swap a disk image, eject the disk image with
+/// set_eject_state(true).
+/// Set the disk index with set_image_index(index).
+/// Insert the disk again with set_eject_state(false).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DiskControlCallback {
+    pub set_eject_state: retro_set_eject_state_t,
+    pub get_eject_state: retro_get_eject_state_t,
+
+    pub get_image_index: retro_get_image_index_t,
+    pub set_image_index: retro_set_image_index_t,
+    pub get_num_images: retro_get_num_images_t,
+
+    pub replace_image_index: retro_replace_image_index_t,
+    pub add_image_index: retro_add_image_index_t,
+}
+
+/// Sets initial image to insert in drive when calling
+/// core_load_game().
+/// Since we cannot pass the initial index when loading
+/// content (this would require a major API change), this
+/// is set by the frontend *before* calling the core's
+/// retro_load_game()/retro_load_game_special() implementation.
+/// A core should therefore cache the index/path values and handle
+/// them inside retro_load_game()/retro_load_game_special().
+/// - If 'index' is invalid (index >= get_num_images()), the
+///   core should ignore the set value and instead use 0
+/// - 'path' is used purely for error checking - i.e. when
+///   content is loaded, the core should verify that the
+///   disk specified by 'index' has the specified file path.
+///   This is to guard against auto selecting the wrong image
+///   if (for example) the user should modify an existing M3U
+///   playlist. We have to let the core handle this because
+///   set_initial_image() must be called before loading content,
+///   i.e. the frontend cannot access image paths in advance
+///   and thus cannot perform the error check itself.
+///   If set path and content path do not match, the core should
+///   ignore the set 'index' and 'path' values and instead use 0
+/// Returns 'false' if index or 'path' are invalid, or core
+/// does not support this functionality
+pub type retro_set_initial_image_t = Option<unsafe extern "C" fn(index: libc::c_uint, path: *const libc::c_char) -> bool>;
+
+/// Fetches the path of the specified disk image file.
+/// Returns 'false' if index is invalid (index >= get_num_images())
+/// or path is otherwise unavailable.
+pub type retro_get_image_path_t =
+    Option<unsafe extern "C" fn(index: libc::c_uint, path: *mut libc::c_char, len: libc::size_t) -> bool>;
+
+/// Fetches a core-provided 'label' for the specified disk
+/// image file. In the simplest case this may be a file name
+/// (without extension), but for cores with more complex
+/// content requirements information may be provided to
+/// facilitate user disk swapping - for example, a core
+/// running floppy-disk-based content may uniquely label
+/// save disks, data disks, level disks, etc. with names
+/// corresponding to in-game disk change prompts (so the
+/// frontend can provide better user guidance than a 'dumb'
+/// disk index value).
+/// Returns 'false' if index is invalid (index >= get_num_images())
+/// or label is otherwise unavailable.
+pub type retro_get_image_label_t =
+    Option<unsafe extern "C" fn(index: libc::c_uint, label: *mut libc::c_char, len: libc::size_t) -> bool>;
+
+/// Callbacks for RETRO_ENVIRONMENT_SET_DISK_CONTROL_EXT_INTERFACE.
+/// Should be set for implementations which can swap out multiple disk
+/// images in runtime.
+///
+/// If the implementation can do this automatically, it should strive to do so.
+/// However, there are cases where the user must manually do so.
+///
+/// Overview: To swap a disk image, eject the disk image with
+/// set_eject_state(true).
+/// Set the disk index with set_image_index(index).
+/// Insert the disk again with set_eject_state(false).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DiskControlExtCallback {
+    pub set_eject_state: retro_set_eject_state_t,
+    pub get_eject_state: retro_get_eject_state_t,
+
+    pub get_image_index: retro_get_image_index_t,
+    pub set_image_index: retro_set_image_index_t,
+    pub get_num_images: retro_get_num_images_t,
+
+    pub replace_image_index: retro_replace_image_index_t,
+    pub add_image_index: retro_add_image_index_t,
+
+    /// NOTE: Frontend will only attempt to record/restore
+    /// last used disk index if both set_initial_image()
+    /// and get_image_path() are implemented
+    pub set_initial_image: retro_set_initial_image_t, // Optional - may be NULL
+
+    pub get_image_path: retro_get_image_path_t,   // Optional - may be NULL
+    pub get_image_label: retro_get_image_label_t, // Optional - may be NULL
+}
+
+/// Pass this to retro_video_refresh_t if rendering to hardware.
+/// Passing NULL to retro_video_refresh_t is still a frame dupe as normal.
+/// Only valid after a successful ENVIRONMENT_SET_HW_RENDER.
+pub const HW_FRAME_BUFFER_VALID: *const libc::c_void = usize::MAX as *const libc::c_void;
+
+/// Invalidates the current HW context.
+/// Any GPU state is lost, and must not be deinitialized explicitly.
+/// If explicit deinitialization is desired by the libretro core,
+/// it should implement context_destroy callback.
+/// If called, all GPU resources must be reinitialized.
+/// Usually called when frontend reinits video driver.
+/// Also called first time video driver is initialized,
+/// allowing libretro core to initialize resources.
+pub type retro_hw_context_reset_t = Option<unsafe extern "C" fn()>;
+
+/// Gets current framebuffer which is to be rendered to.
+/// Could change every frame potentially.
+pub type retro_hw_get_current_framebuffer_t = Option<unsafe extern "C" fn() -> libc::uintptr_t>;
+
+/// Libretro API extension functions returned by retro_hw_get_proc_address_t.
+pub type retro_proc_address_t = Option<unsafe extern "C" fn()>;
+
+/// Get a symbol from HW context.
+pub type retro_hw_get_proc_address_t = Option<unsafe extern "C" fn(sym: *const libc::c_char) -> retro_proc_address_t>;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HwContextType {
+    None = 0,
+
+    /// OpenGL 2.x. Driver can choose to use latest compatibility context.
+    OpenGl = 1,
+
+    /// OpenGL ES 2.0.
+    OpenGlEs2 = 2,
+
+    /// Modern desktop core GL context. Use version_major/
+    /// version_minor fields to set GL version.
+    OpenGlCore = 3,
+
+    /// OpenGL ES 3.0
+    OpenGlEs3 = 4,
+
+    /// OpenGL ES 3.1+. Set version_major/version_minor. For GLES2 and GLES3,
+    /// use the corresponding enums directly.
+    OpenGlEsVersion = 5,
+
+    /// Vulkan, see RETRO_ENVIRONMENT_SET_HW_RENDER_CONTEXT_NEGOTIATION_INTERFACE.
+    Vulkan = 6,
+
+    /// Direct3D, set version_major to select the type of interface
+    /// returned by RETRO_ENVIRONMENT_GET_HW_RENDER_INTERFACE
+    D3D11 = 7,
+    D3D10 = 8,
+    D3D12 = 9,
+    D3D9 = 10,
+
+    Dummy = i32::MAX as isize,
+}
+
+impl TryFrom<libc::c_uint> for HwContextType {
+    type Error = libc::c_uint;
+
+    /// Rejects the `Dummy` sentinel and unknown values.
+    fn try_from(value: libc::c_uint) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => HwContextType::None,
+            1 => HwContextType::OpenGl,
+            2 => HwContextType::OpenGlEs2,
+            3 => HwContextType::OpenGlCore,
+            4 => HwContextType::OpenGlEs3,
+            5 => HwContextType::OpenGlEsVersion,
+            6 => HwContextType::Vulkan,
+            7 => HwContextType::D3D11,
+            8 => HwContextType::D3D10,
+            9 => HwContextType::D3D12,
+            10 => HwContextType::D3D9,
+            _ => return Err(value),
+        })
+    }
+}
+
+impl From<HwContextType> for libc::c_uint {
+    fn from(context_type: HwContextType) -> Self {
+        context_type as libc::c_uint
+    }
+}
+
+impl HwContextType {
+    /// True for the OpenGL ES contexts.
+    pub fn is_gles(self) -> bool {
+        matches!(self, HwContextType::OpenGlEs2 | HwContextType::OpenGlEs3 | HwContextType::OpenGlEsVersion)
+    }
+
+    /// Whether the frontend reads version_major/version_minor for this
+    /// context type. The other types either imply a version or ignore them.
+    pub fn version_fields_meaningful(self) -> bool {
+        matches!(self, HwContextType::OpenGlCore | HwContextType::OpenGlEsVersion)
+    }
+}
+
+/// Picks the hardware context to request with ENVIRONMENT_SET_HW_RENDER:
+/// the frontend's preference from ENVIRONMENT_GET_PREFERRED_HW_RENDER if
+/// `supported` contains it, otherwise the first entry of `supported`.
+/// Returns None only when `supported` is empty.
+///
+/// # Safety
+/// `cb` must be NULL or the environment callback given to the core.
+pub unsafe fn negotiate_context(cb: retro_environment_t, supported: &[HwContextType]) -> Option<HwContextType> {
+    let mut preferred: libc::c_uint = HwContextType::None.into();
+    let asked = match cb {
+        Some(cb) => cb(ENVIRONMENT_GET_PREFERRED_HW_RENDER, (&mut preferred as *mut libc::c_uint).cast()),
+        None => false,
+    };
+    asked
+        .then(|| HwContextType::try_from(preferred).ok())
+        .flatten()
+        .filter(|preferred| supported.contains(preferred))
+        .or_else(|| supported.first().copied())
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct HwRenderCallback {
+    /// Which API to use. Set by libretro core, see [`HwContextType`].
+    pub context_type: libc::c_uint,
+
+    /// Called when a context has been created or when it has been reset.
+    /// An OpenGL context is only valid after context_reset() has been called.
+    ///
+    /// When context_reset is called, OpenGL resources in the libretro
+    /// implementation are guaranteed to be invalid.
+    ///
+    /// It is possible that context_reset is called multiple times during an
+    /// application lifecycle.
+    /// If context_reset is called without any notification (context_destroy),
+    /// the OpenGL context was lost and resources should just be recreated
+    /// without any attempt to "free" old resources.
+    pub context_reset: retro_hw_context_reset_t,
+
+    /// Set by frontend.
+    /// TODO: This is rather obsolete. The frontend should not
+    /// be providing preallocated framebuffers.
+    pub get_current_framebuffer: retro_hw_get_current_framebuffer_t,
+
+    /// Set by frontend.
+    /// Can return all relevant functions, including glClear on Windows.
+    pub get_proc_address: retro_hw_get_proc_address_t,
+
+    /// Set if render buffers should have depth component attached.
+    /// TODO: Obsolete.
+    pub depth: bool,
+
+    /// Set if stencil buffers should be attached.
+    /// TODO: Obsolete.
+    ///
+    /// If depth and stencil are true, a packed 24/8 buffer will be added.
+    /// Only attaching stencil is invalid and will be ignored.
+    pub stencil: bool,
+
+    /// Use conventional bottom-left origin convention. If false,
+    /// standard libretro top-left origin semantics are used.
+    /// TODO: Move to GL specific interface.
+    pub bottom_left_origin: bool,
+
+    /// Major version number for core GL context or GLES 3.1+.
+    pub version_major: libc::c_uint,
+
+    /// Minor version number for core GL context or GLES 3.1+.
+    pub version_minor: libc::c_uint,
+
+    /// If this is true, the frontend will go very far to avoid
+    /// resetting context in scenarios like toggling fullscreen, etc.
+    /// TODO: Obsolete? Maybe frontend should just always assume this ...
+    ///
+    /// The reset callback might still be called in extreme situations
+    /// such as if the context is lost beyond recovery.
+    ///
+    /// For optimal stability, set this to false, and allow context to be
+    /// reset at any time.
+    pub cache_context: bool,
+
+    /// A callback to be called before the context is destroyed in a
+    /// controlled way by the frontend.
+    ///
+    /// OpenGL resources can be deinitialized cleanly at this step.
+    /// context_destroy can be set to NULL, in which resources will
+    /// just be destroyed without any notification.
+    ///
+    /// Even when context_destroy is non-NULL, it is possible that
+    /// context_reset is called without any destroy notification.
+    /// This happens if context is lost by external factors (such as
+    /// notified by GL_ARB_robustness).
+    ///
+    /// In this case, the context is assumed to be already dead,
+    /// and the libretro implementation must not try to free any OpenGL
+    /// resources in the subsequent context_reset.
+    pub context_destroy: retro_hw_context_reset_t,
+
+    /// Creates a debug context.
+    pub debug_context: bool,
+}
+
+impl HwRenderCallback {
+    /// Request for `context_type` with the given context version and
+    /// every other field cleared; the frontend fills in its callbacks.
+    /// OpenGlEsVersion has no implied version, so a major version of 0
+    /// is a debug assertion failure there.
+    pub fn new(context_type: HwContextType, version_major: libc::c_uint, version_minor: libc::c_uint) -> Self {
+        debug_assert!(
+            context_type != HwContextType::OpenGlEsVersion || version_major > 0,
+            "OpenGlEsVersion contexts require version_major to be set"
+        );
+        Self {
+            context_type: context_type.into(),
+            context_reset: None,
+            get_current_framebuffer: None,
+            get_proc_address: None,
+            depth: false,
+            stencil: false,
+            bottom_left_origin: false,
+            version_major,
+            version_minor,
+            cache_context: false,
+            context_destroy: None,
+            debug_context: false,
+        }
+    }
+
+    /// Requests a debug context, e.g. `.debug(cfg!(debug_assertions))`.
+    pub fn debug(mut self, enabled: bool) -> Self {
+        self.debug_context = enabled;
+        self
+    }
+
+    /// Whether a debug context is requested.
+    pub fn wants_debug_context(&self) -> bool {
+        self.debug_context
+    }
+
+    /// Requests a depth attachment on the frontend's framebuffer.
+    pub fn depth_buffer(mut self, depth: bool) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Requests a stencil attachment; it is only honored together with
+    /// a depth attachment, as a packed 24/8 buffer.
+    pub fn stencil_buffer(mut self, stencil: bool) -> Self {
+        self.stencil = stencil;
+        self
+    }
+
+    /// The `(depth, stencil)` attachments the frontend will create.
+    /// A stencil request without depth is ignored by frontends, so it
+    /// yields no attachments.
+    pub fn required_attachments(&self) -> (bool, bool) {
+        (self.depth, self.depth && self.stencil)
+    }
+
+    /// Whether the core asked the frontend to keep its context alive
+    /// across window resizes and fullscreen toggles. Cores whose
+    /// context_reset is expensive (shader compilation, large texture
+    /// uploads) should set `cache_context`; cores that can rebuild
+    /// cheaply are more robust leaving it false.
+    pub fn should_cache_context(&self) -> bool {
+        self.cache_context
+    }
+}
+
+/// Converts an analog stick position into digital directions, as
+/// `(up, down, left, right)`. An axis only counts once it goes past
+/// `threshold` in either direction; positive Y is down.
+pub fn analog_to_dpad(x: i16, y: i16, threshold: i16) -> (bool, bool, bool, bool) {
+    let (x, y, threshold) = (i32::from(x), i32::from(y), i32::from(threshold));
+    (y < -threshold, y > threshold, x < -threshold, x > threshold)
+}
+
+/// Normalizes an analog stick position to [-1, 1] on each axis with a
+/// radial dead zone. `deadzone` is a fraction of full deflection in
+/// [0, 1); positions inside it read as (0, 0), and the remaining range
+/// is rescaled so leaving the dead zone starts from 0 and full
+/// deflection still reaches 1.
+pub fn analog_with_deadzone(x: i16, y: i16, deadzone: f32) -> (f32, f32) {
+    let deadzone = deadzone.clamp(0.0, 0.999);
+    let x = (f32::from(x) / 32767.0).clamp(-1.0, 1.0);
+    let y = (f32::from(y) / 32767.0).clamp(-1.0, 1.0);
+    let magnitude = x.hypot(y);
+    if magnitude <= deadzone {
+        return (0.0, 0.0);
+    }
+    let scaled = ((magnitude - deadzone) / (1.0 - deadzone)).min(1.0);
+    (x / magnitude * scaled, y / magnitude * scaled)
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RumbleEffect {
+    Strong = 0,
+    Weak = 1,
+
+    Dummy = i32::MAX as isize,
+}
+
+/// Sets rumble state for joypad plugged in port 'port'.
+/// Rumble effects are controlled independently,
+/// and setting e.g. strong rumble does not override weak rumble.
+/// Strength has a range of [0, 0xffff].
+///
+/// Returns true if rumble state request was honored.
+/// Calling this before first retro_run() is likely to return false.
+pub type retro_set_rumble_state_t =
+    Option<unsafe extern "C" fn(port: libc::c_uint, effect: libc::c_uint, strength: u16) -> bool>;
+
+/// Filled in by ENVIRONMENT_GET_RUMBLE_INTERFACE:
+///
+/// ```
+/// use std::ffi::c_uint;
+/// use libretro_sys::{RumbleEffect, RumbleInterface};
+///
+/// fn pulse(rumble: &RumbleInterface) {
+///     // Strong motor of the first controller at full strength.
+///     if let Some(set_rumble_state) = rumble.set_rumble_state {
+///         unsafe { set_rumble_state(0, RumbleEffect::Strong as c_uint, 0xffff) };
+///     }
+/// }
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RumbleInterface {
+    pub set_rumble_state: retro_set_rumble_state_t,
+}
+
+impl RumbleInterface {
+    /// Sets both motors of the joypad in `port`, strong first. Returns
+    /// true only if the frontend honored both requests, and false without
+    /// calling anything if `set_rumble_state` is NULL.
+    ///
+    /// # Safety
+    /// `set_rumble_state` must be NULL or the frontend's callback.
+    pub unsafe fn set_both(&self, port: libc::c_uint, strong: u16, weak: u16) -> bool {
+        let Some(set_rumble_state) = self.set_rumble_state else {
+            return false;
+        };
+        let strong = set_rumble_state(port, RumbleEffect::Strong as libc::c_uint, strong);
+        let weak = set_rumble_state(port, RumbleEffect::Weak as libc::c_uint, weak);
+        strong && weak
+    }
+}
+
+/// FIXME: Document the sensor API and work out behavior.
+/// It will be marked as experimental until then.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SensorAction {
+    AccelerometerEnable = 0,
+    AccelerometerDisable,
+    GyroscopeEnable,
+    GyroscopeDisable,
+    IlluminanceEnable,
+    IlluminanceDisable,
+
+    Dummy = i32::MAX as isize,
+}
+
+/// Id values for SENSOR types.
+pub const SENSOR_ACCELEROMETER_X: libc::c_uint = 0;
+pub const SENSOR_ACCELEROMETER_Y: libc::c_uint = 1;
+pub const SENSOR_ACCELEROMETER_Z: libc::c_uint = 2;
+pub const SENSOR_GYROSCOPE_X: libc::c_uint = 3;
+pub const SENSOR_GYROSCOPE_Y: libc::c_uint = 4;
+pub const SENSOR_GYROSCOPE_Z: libc::c_uint = 5;
+pub const SENSOR_ILLUMINANCE: libc::c_uint = 6;
+
+pub type retro_set_sensor_state_t =
+    Option<unsafe extern "C" fn(port: libc::c_uint, action: libc::c_uint, rate: libc::c_uint) -> bool>;
+
+pub type retro_sensor_get_input_t = Option<unsafe extern "C" fn(port: libc::c_uint, id: libc::c_uint) -> libc::c_float>;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SensorInterface {
+    pub set_sensor_state: retro_set_sensor_state_t,
+    pub get_sensor_input: retro_sensor_get_input_t,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CameraBuffer {
+    OpenglTexture = 0,
+    RawFramebuffer = 1,
+
+    Dummy = i32::MAX as isize,
+}
+
+/// Starts the camera driver. Can only be called in retro_run().
+pub type retro_camera_start_t = Option<unsafe extern "C" fn() -> bool>;
+
+/// Stops the camera driver. Can only be called in retro_run().
+pub type retro_camera_stop_t = Option<unsafe extern "C" fn()>;
+
+/// Callback which signals when the camera driver is initialized
+/// and/or deinitialized.
+/// retro_camera_start_t can be called in initialized callback.
+pub type retro_camera_lifetime_status_t = Option<unsafe extern "C" fn()>;
+
+/// A callback for raw framebuffer data. buffer points to an XRGB8888 buffer.
+/// Width, height and pitch are similar to retro_video_refresh_t.
+/// First pixel is top-left origin.
+pub type retro_camera_frame_raw_framebuffer_t =
+    Option<unsafe extern "C" fn(buffer: *const u32, width: libc::c_uint, height: libc::c_uint, pitch: libc::size_t)>;
+
+/// A callback for when OpenGL textures are used.
+///
+/// texture_id is a texture owned by camera driver.
+/// Its state or content should be considered immutable, except for things like
+/// texture filtering and clamping.
+///
+/// texture_target is the texture target for the GL texture.
+/// These can include e.g. GL_TEXTURE_2D, GL_TEXTURE_RECTANGLE, and possibly
+/// more depending on extensions.
+///
+/// affine points to a packed 3x3 column-major matrix used to apply an affine
+/// transform to texture coordinates. (affine_matrix * vec3(coord_x, coord_y, 1.0))
+/// After transform, normalized texture coord (0, 0) should be bottom-left
+/// and (1, 1) should be top-right (or (width, height) for RECTANGLE).
+///
+/// GL-specific typedefs are avoided here to avoid relying on gl.h in
+/// the API definition.
+pub type retro_camera_frame_opengl_texture_t =
+    Option<unsafe extern "C" fn(texture_id: libc::c_uint, texture_target: libc::c_uint, affine: *const libc::c_float)>;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CameraCallback {
+    /// Set by libretro core.
+    /// Example bitmask: caps = (1 << RETRO_CAMERA_BUFFER_OPENGL_TEXTURE) | (1 << RETRO_CAMERA_BUFFER_RAW_FRAMEBUFFER).
+    pub caps: u64,
+
+    /// Desired resolution for camera. Is only used as a hint.
+    pub width: libc::c_uint,
+    pub height: libc::c_uint,
+
+    /// Set by frontend.
+    pub start: retro_camera_start_t,
+    pub stop: retro_camera_stop_t,
+
+    /// Set by libretro core if raw framebuffer callbacks will be used.
+    pub frame_raw_framebuffer: retro_camera_frame_raw_framebuffer_t,
+
+    /// Set by libretro core if OpenGL texture callbacks will be used.
+    pub frame_opengl_texture: retro_camera_frame_opengl_texture_t,
+
+    /// Set by libretro core. Called after camera driver is initialized and
+    /// ready to be started.
+    /// Can be NULL, in which this callback is not called.
+    pub initialized: retro_camera_lifetime_status_t,
+
+    /// Set by libretro core. Called right before camera driver is
+    /// deinitialized.
+    /// Can be NULL, in which this callback is not called.
+    pub deinitialized: retro_camera_lifetime_status_t,
+}
+
+/// A raw camera frame: XRGB8888 pixels with a top-left origin. `pitch`
+/// is in bytes, as with retro_video_refresh_t.
+#[derive(Clone, Copy, Debug)]
+pub struct CameraFrame<'a> {
+    pub pixels: &'a [u32],
+    pub width: libc::c_uint,
+    pub height: libc::c_uint,
+    pub pitch: libc::size_t,
+}
+
+/// Receives camera frames through the [`Camera`] trampolines.
+pub trait CameraHandler: Send {
+    /// A frame delivered through frame_raw_framebuffer.
+    fn on_raw_frame(&mut self, frame: CameraFrame<'_>);
+
+    /// A frame delivered through frame_opengl_texture. `affine` points
+    /// to a packed 3x3 column-major texture coordinate transform.
+    fn on_gl_texture(&mut self, texture_id: libc::c_uint, texture_target: libc::c_uint, affine: *const libc::c_float);
+}
+
+static CAMERA_HANDLER: Mutex<Option<Box<dyn CameraHandler>>> = Mutex::new(None);
+
+/// Owner of the [`CameraHandler`] fed by the camera frame callbacks.
+/// The handler only ever runs while holding a lock, so it must not
+/// call [`Camera::set`] or [`Camera::clear`].
+pub struct Camera;
+
+impl Camera {
+    /// Installs the handler run by the trampolines, replacing any
+    /// previous one.
+    pub fn set<H>(handler: H)
+    where
+        H: CameraHandler + 'static,
+    {
+        *CAMERA_HANDLER.lock().unwrap_or_else(PoisonError::into_inner) = Some(Box::new(handler));
+    }
+
+    /// Removes the installed handler; later frames are dropped.
+    pub fn clear() {
+        *CAMERA_HANDLER.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    /// Request for ENVIRONMENT_GET_CAMERA_INTERFACE with the frame
+    /// callbacks for each [`CameraBuffer`] bit in `caps` pointing at the
+    /// trampolines. The frontend fills in start and stop.
+    pub fn callback(caps: u64, width: libc::c_uint, height: libc::c_uint) -> CameraCallback {
+        let wants = |buffer: CameraBuffer| caps & (1 << buffer as u64) != 0;
+        CameraCallback {
+            caps,
+            width,
+            height,
+            frame_raw_framebuffer: wants(CameraBuffer::RawFramebuffer).then_some(Camera::raw_trampoline as _),
+            frame_opengl_texture: wants(CameraBuffer::OpenglTexture).then_some(Camera::gl_trampoline as _),
+            ..Default::default()
+        }
+    }
+
+    /// Starts the camera driver through the frontend's `start`.
+    /// Returns false when the frontend did not provide one.
+    ///
+    /// # Safety
+    /// `cb` must have been filled in by ENVIRONMENT_GET_CAMERA_INTERFACE,
+    /// and this may only be called from retro_run or `initialized`.
+    pub unsafe fn start(cb: &CameraCallback) -> bool {
+        cb.start.is_some_and(|start| start())
+    }
+
+    /// Stops the camera driver through the frontend's `stop`.
+    ///
+    /// # Safety
+    /// `cb` must have been filled in by ENVIRONMENT_GET_CAMERA_INTERFACE,
+    /// and this may only be called from retro_run.
+    pub unsafe fn stop(cb: &CameraCallback) {
+        if let Some(stop) = cb.stop {
+            stop();
+        }
+    }
+
+    /// Forwards a raw frame to the handler. NULL or empty frames are
+    /// dropped.
+    ///
+    /// # Safety
+    /// `buffer` must be NULL or point to `height` rows of `pitch` bytes,
+    /// the last of which holds at least `width` pixels.
+    pub unsafe extern "C" fn raw_trampoline(
+        buffer: *const u32,
+        width: libc::c_uint,
+        height: libc::c_uint,
+        pitch: libc::size_t,
+    ) {
+        if buffer.is_null() || width == 0 || height == 0 {
+            return;
+        }
+        let len = pitch / 4 * (height as usize - 1) + width as usize;
+        let frame = CameraFrame { pixels: std::slice::from_raw_parts(buffer, len), width, height, pitch };
+        let mut handler = CAMERA_HANDLER.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(handler) = handler.as_mut() {
+            handler.on_raw_frame(frame);
+        }
+    }
+
+    /// Forwards an OpenGL texture frame to the handler.
+    pub extern "C" fn gl_trampoline(texture_id: libc::c_uint, texture_target: libc::c_uint, affine: *const libc::c_float) {
+        let mut handler = CAMERA_HANDLER.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(handler) = handler.as_mut() {
+            handler.on_gl_texture(texture_id, texture_target, affine);
+        }
+    }
+}
+
+/// Keys declared by more than one option definition, each reported once
+/// in the order of its first repetition. Frontends keep only one of the
+/// clashing options, so cores can assert this is empty in their tests.
+///
+/// # Safety
+/// `opts.definitions` must be NULL or point to a definitions array
+/// terminated by an entry with a NULL key, with every key a valid C string.
+pub unsafe fn find_duplicate_keys(opts: &CoreOptionsV2) -> Vec<&CStr> {
+    let mut seen = BTreeSet::new();
+    let mut duplicates = Vec::new();
+    for def in terminated_slice(opts.definitions, |def| def.key.is_null()) {
+        let key = CStr::from_ptr(def.key);
+        if !seen.insert(key) && !duplicates.contains(&key) {
+            duplicates.push(key);
+        }
+    }
+    duplicates
+}
+
+#[derive(Clone, Debug)]
+struct StoredOption {
+    values: Vec<CString>,
+    current: CString,
+}
+
+/// Number of values declared by `def`: the entries before the first
+/// NULL value, at most NUM_CORE_OPTION_VALUES_MAX.
+pub fn option_value_count(def: &CoreOptionV2Definition) -> usize {
+    def.values.iter().take_while(|value| !value.value.is_null()).count()
+}
+
+/// Values of `def` in declaration order, up to the first NULL value.
+///
+/// # Safety
+/// Every non-NULL value string must be a valid C string.
+unsafe fn option_values(def: &CoreOptionV2Definition) -> impl Iterator<Item = &CStr> {
+    def.values[..option_value_count(def)].iter().map(|value| CStr::from_ptr(value.value))
+}
+
+/// The value a frontend starts `def` at: its default_value if that is
+/// one of its values, otherwise its first value. None if it has no values.
+///
+/// # Safety
+/// Every non-NULL string in `def` must be a valid C string.
+unsafe fn effective_default(def: &CoreOptionV2Definition) -> Option<&CStr> {
+    optional_cstr(def.default_value)
+        .filter(|default| option_values(def).any(|value| value == *default))
+        .or_else(|| option_values(def).next())
+}
+
+/// Each option's key paired with the value a frontend starts it at.
+/// A default that is not one of the option's values falls back to the
+/// first value; options without any values are skipped.
+///
+/// # Safety
+/// `opts.definitions` must be NULL or point to a definitions array
+/// terminated by an entry with a NULL key, and every non-NULL string
+/// in it must be a valid C string, all valid for the borrow of `opts`.
+pub unsafe fn option_defaults(opts: &CoreOptionsV2) -> Vec<(&CStr, &CStr)> {
+    terminated_slice(opts.definitions, |def| def.key.is_null())
+        .iter()
+        .filter_map(|def| Some((CStr::from_ptr(def.key), effective_default(def)?)))
+        .collect()
+}
+
+/// Renders each option as a `key = default` line, preceded by a comment
+/// listing its values, in the format read by [`parse_options_text`].
+/// Defaults follow [`option_defaults`]; options without values are skipped.
+///
+/// # Safety
+/// Same requirements as [`option_defaults`].
+pub unsafe fn options_to_text(opts: &CoreOptionsV2) -> String {
+    let mut text = String::new();
+    for def in terminated_slice(opts.definitions, |def| def.key.is_null()) {
+        let Some(default) = effective_default(def) else {
+            continue;
+        };
+        let values: Vec<_> = option_values(def).map(CStr::to_string_lossy).collect();
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&format!(
+            "# {}\n{} = {}\n",
+            values.join("|"),
+            CStr::from_ptr(def.key).to_string_lossy(),
+            default.to_string_lossy()
+        ));
+    }
+    text
+}
+
+/// The `(key, value)` pairs of an options file written by
+/// [`options_to_text`]. Blank lines, `#` comments and lines without
+/// `=` are skipped; keys and values are trimmed.
+pub fn parse_options_text(text: &str) -> Vec<(&str, &str)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect()
+}
+
+/// Frontend-side record of the values currently selected for each core
+/// option, used to answer GET_VARIABLE. Values are copied out of the
+/// core's tables, so the store does not borrow them.
+#[derive(Clone, Debug, Default)]
+pub struct OptionStore {
+    options: BTreeMap<CString, StoredOption>,
+}
+
+impl OptionStore {
+    /// Builds a store holding each option's default value. A default
+    /// that is not one of the option's values is ignored in favour of
+    /// the first value, as the frontend would. Options without any
+    /// values are skipped.
+    ///
+    /// # Safety
+    /// `opts.definitions` must be NULL or point to a definitions array
+    /// terminated by an entry with a NULL key, and every non-NULL string
+    /// in it must be a valid C string.
+    pub unsafe fn from_options_v2(opts: &CoreOptionsV2) -> Self {
+        let mut options = BTreeMap::new();
+        for def in terminated_slice(opts.definitions, |def| def.key.is_null()) {
+            let Some(current) = effective_default(def) else {
+                continue;
+            };
+            let values = option_values(def).map(CStr::to_owned).collect();
+            let current = current.to_owned();
+            options.insert(CStr::from_ptr(def.key).to_owned(), StoredOption { values, current });
+        }
+        Self { options }
+    }
+
+    /// Current value of `key`, None if the key is unknown.
+    pub fn get(&self, key: &CStr) -> Option<&CStr> {
+        self.options.get(key).map(|option| option.current.as_c_str())
+    }
+
+    /// Selects `value` for `key`. Returns false, leaving the store
+    /// unchanged, if the key is unknown or `value` is not one of its values.
+    pub fn set(&mut self, key: &CStr, value: &CStr) -> bool {
+        match self.options.get_mut(key) {
+            Some(option) if option.values.iter().any(|v| v.as_c_str() == value) => {
+                option.current = value.to_owned();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Allowed values of `key` in declaration order.
+    pub fn values(&self, key: &CStr) -> Option<impl Iterator<Item = &CStr>> {
+        self.options.get(key).map(|option| option.values.iter().map(CString::as_c_str))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &CStr> {
+        self.options.keys().map(CString::as_c_str)
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug = 0,
+    Info,
+    Warn,
+    Error,
+
+    Dummy = i32::MAX as isize,
+}
+
+/// The `log` crate level matching `level`. The Dummy sentinel maps to
+/// Trace, below every real level.
+#[cfg(feature = "log")]
+pub fn to_log_level(level: LogLevel) -> log::Level {
+    match level {
+        LogLevel::Debug => log::Level::Debug,
+        LogLevel::Info => log::Level::Info,
+        LogLevel::Warn => log::Level::Warn,
+        LogLevel::Error => log::Level::Error,
+        LogLevel::Dummy => log::Level::Trace,
+    }
+}
+
+/// The LogLevel matching a `log` crate level. libretro has no trace
+/// level, so Trace is logged as Debug.
+#[cfg(feature = "log")]
+pub fn from_log_level(level: log::Level) -> LogLevel {
+    match level {
+        log::Level::Trace | log::Level::Debug => LogLevel::Debug,
+        log::Level::Info => LogLevel::Info,
+        log::Level::Warn => LogLevel::Warn,
+        log::Level::Error => LogLevel::Error,
+    }
+}
+
+/// Logging function. Takes log level argument as well.
+pub type retro_log_printf_t = Option<unsafe extern "C" fn(level: libc::c_uint, fmt: *const libc::c_char, ...)>;
+
+/// Filled in by ENVIRONMENT_GET_LOG_INTERFACE. Messages go through a
+/// printf-style format string, so pass text as a `%s` argument:
+///
+/// ```
+/// use std::ffi::c_uint;
+/// use libretro_sys::{LogCallback, LogLevel};
+///
+/// fn log_bios_loaded(logging: &LogCallback) {
+///     match logging.log {
+///         Some(log) => unsafe { log(LogLevel::Info as c_uint, c"%s\n".as_ptr(), c"Loaded BIOS".as_ptr()) },
+///         None => eprintln!("Loaded BIOS"),
+///     }
+/// }
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogCallback {
+    pub log: retro_log_printf_t,
+}
+
+/// ID values for SIMD CPU features
+pub const SIMD_SSE: u64 = 1 << 0;
+pub const SIMD_SSE2: u64 = 1 << 1;
+pub const SIMD_VMX: u64 = 1 << 2;
+pub const SIMD_VMX128: u64 = 1 << 3;
+pub const SIMD_AVX: u64 = 1 << 4;
+pub const SIMD_NEON: u64 = 1 << 5;
+pub const SIMD_SSE3: u64 = 1 << 6;
+pub const SIMD_SSSE3: u64 = 1 << 7;
+pub const SIMD_MMX: u64 = 1 << 8;
+pub const SIMD_MMXEXT: u64 = 1 << 9;
+pub const SIMD_SSE4: u64 = 1 << 10;
+pub const SIMD_SSE42: u64 = 1 << 11;
+pub const SIMD_AVX2: u64 = 1 << 12;
+pub const SIMD_VFPU: u64 = 1 << 13;
+pub const SIMD_PS: u64 = 1 << 14;
+pub const SIMD_AES: u64 = 1 << 15;
+pub const SIMD_VFPV3: u64 = 1 << 16;
+pub const SIMD_VFPV4: u64 = 1 << 17;
+pub const SIMD_POPCNT: u64 = 1 << 18;
+pub const SIMD_MOVBE: u64 = 1 << 19;
+pub const SIMD_CMOV: u64 = 1 << 20;
+pub const SIMD_ASIMD: u64 = 1 << 21;
+
+pub type retro_time_t = i64;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PerfCounter {
+    pub ident: *const libc::c_char,
+    pub start: retro_perf_tick_t,
+    pub total: retro_perf_tick_t,
+    pub call_cnt: retro_perf_tick_t,
+
+    pub registered: bool,
+}
+
+impl Default for PerfCounter {
+    fn default() -> Self {
+        Self {
+            ident: std::ptr::null(),
+            start: 0,
+            total: 0,
+            call_cnt: 0,
+            registered: false,
+        }
+    }
+}
+
+/// Returns current time in microseconds.
+/// Tries to use the most accurate timer available.
+pub type retro_perf_get_time_usec_t = Option<unsafe extern "C" fn() -> retro_time_t>;
+
+/// A simple counter. Usually nanoseconds, but can also be CPU cycles.
+/// Can be used directly if desired (when creating a more sophisticated
+/// performance counter system).
+pub type retro_perf_get_counter_t = Option<unsafe extern "C" fn() -> retro_perf_tick_t>;
+
+/// Returns a bit-mask of detected CPU features (RETRO_SIMD_*).
+pub type retro_get_cpu_features_t = Option<unsafe extern "C" fn() -> u64>;
+
+/// Asks frontend to log and/or display the state of performance counters.
+/// Performance counters can always be poked into manually as well.
+pub type retro_perf_log_t = Option<unsafe extern "C" fn()>;
+
+/// Register a performance counter.
+/// ident field must be set with a discrete value and other values in
+/// retro_perf_counter must be 0.
+/// Registering can be called multiple times. To avoid calling to
+/// frontend redundantly, you can check registered field first.
+pub type retro_perf_register_t = Option<unsafe extern "C" fn(counter: *mut PerfCounter)>;
+
+/// Starts a registered counter.
+pub type retro_perf_start_t = Option<unsafe extern "C" fn(counter: *mut PerfCounter)>;
+
+/// Stops a registered counter.
+pub type retro_perf_stop_t = Option<unsafe extern "C" fn(counter: *mut PerfCounter)>;
+
+/// For convenience it can be useful to wrap register, start and stop in macros.
+/// E.g.:
+/// #ifdef LOG_PERFORMANCE
+/// #define RETRO_PERFORMANCE_INIT(perf_cb, name) static struct retro_perf_counter name = {#name}; if (!name.registered) perf_cb.perf_register(&(name))
+/// #define RETRO_PERFORMANCE_START(perf_cb, name) perf_cb.perf_start(&(name))
+/// #define RETRO_PERFORMANCE_STOP(perf_cb, name) perf_cb.perf_stop(&(name))
+/// #else
+/// ... Blank macros ...
+/// #endif
+///
+/// These can then be used mid-functions around code snippets.
+///
+/// extern struct retro_perf_callback perf_cb;  * Somewhere in the core.
+///
+/// void do_some_heavy_work(void)
+/// {
+///    RETRO_PERFORMANCE_INIT(cb, work_1;
+///    RETRO_PERFORMANCE_START(cb, work_1);
+///    heavy_work_1();
+///    RETRO_PERFORMANCE_STOP(cb, work_1);
+///
+///    RETRO_PERFORMANCE_INIT(cb, work_2);
+///    RETRO_PERFORMANCE_START(cb, work_2);
+///    heavy_work_2();
+///    RETRO_PERFORMANCE_STOP(cb, work_2);
+/// }
+///
+/// void retro_deinit(void)
+/// {
+///    perf_cb.perf_log();  * Log all perf counters here for example.
+/// }
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PerfCallback {
+    pub get_time_usec: retro_perf_get_time_usec_t,
+    pub get_cpu_features: retro_get_cpu_features_t,
+
+    pub get_perf_counter: retro_perf_get_counter_t,
+    pub perf_register: retro_perf_register_t,
+    pub perf_start: retro_perf_start_t,
+    pub perf_stop: retro_perf_stop_t,
+    pub perf_log: retro_perf_log_t,
+}
+
+/// Timestamps from the frontend's perf interface at finer than the
+/// microsecond resolution of get_time_usec.
+#[derive(Clone, Copy, Debug)]
+pub struct PerfClock {
+    callback: PerfCallback,
+}
+
+impl PerfClock {
+    /// Wraps a perf interface filled in by ENVIRONMENT_GET_PERF_INTERFACE.
+    pub fn new(callback: PerfCallback) -> Self {
+        Self { callback }
+    }
+
+    /// Current perf counter, None if the frontend did not provide one.
+    ///
+    /// # Safety
+    /// The wrapped callbacks must be NULL or the frontend's.
+    pub unsafe fn ticks(&self) -> Option<retro_perf_tick_t> {
+        self.callback.get_perf_counter.map(|get_perf_counter| get_perf_counter())
+    }
+
+    /// Current time in microseconds, None if the frontend did not
+    /// provide a clock.
+    ///
+    /// # Safety
+    /// The wrapped callbacks must be NULL or the frontend's.
+    pub unsafe fn time_usec(&self) -> Option<retro_time_t> {
+        self.callback.get_time_usec.map(|get_time_usec| get_time_usec())
+    }
+
+    /// Nanoseconds between two [`PerfClock::ticks`] reads, given the
+    /// counter rate in ticks per second. The perf interface does not
+    /// report the rate; estimate it by reading both ticks and time_usec
+    /// at the start and end of an interval: `ticks * 1_000_000 / usec`.
+    /// Returns 0 when `tick_rate` is 0.
+    pub fn delta_ns(&self, start_ticks: retro_perf_tick_t, end_ticks: retro_perf_tick_t, tick_rate: u64) -> u64 {
+        if tick_rate == 0 {
+            return 0;
+        }
+        let ns = u128::from(ticks_elapsed(start_ticks, end_ticks)) * 1_000_000_000 / u128::from(tick_rate);
+        u64::try_from(ns).unwrap_or(u64::MAX)
+    }
+}
+
+/// Sets the interval of time and/or distance at which to update/poll
+/// location-based data.
+///
+/// To ensure compatibility with all location-based implementations,
+/// values for both interval_ms and interval_distance should be provided.
+///
+/// interval_ms is the interval expressed in milliseconds.
+/// interval_distance is the distance interval expressed in meters.
+pub type retro_location_set_interval_t =
+    Option<unsafe extern "C" fn(interval_ms: libc::c_uint, interval_distance: libc::c_uint)>;
+
+/// Start location services. The device will start listening for changes to the
+/// current location at regular intervals (which are defined with
+/// retro_location_set_interval_t).
+pub type retro_location_start_t = Option<unsafe extern "C" fn() -> bool>;
+
+/// Stop location services. The device will stop listening for changes
+/// to the current location.
+pub type retro_location_stop_t = Option<unsafe extern "C" fn()>;
+
+/// Get the position of the current location. Will set parameters to
+/// 0 if no new  location update has happened since the last time.
+pub type retro_location_get_position_t =
+    Option<unsafe extern "C" fn(lat: *mut f64, lon: *mut f64, horiz_accuracy: *mut f64, vert_accuracy: *mut f64) -> bool>;
+
+/// Callback which signals when the location driver is initialized
+/// and/or deinitialized.
+/// retro_location_start_t can be called in initialized callback.
+pub type retro_location_lifetime_status_t = Option<unsafe extern "C" fn()>;
+
+/// Filled in by ENVIRONMENT_GET_LOCATION_INTERFACE. Polling GPS once
+/// per frame:
+///
+/// ```
+/// use libretro_sys::LocationCallback;
+///
+/// fn poll_location(location: &LocationCallback) -> Option<(f64, f64)> {
+///     let get_position = location.get_position?;
+///     let (mut lat, mut lon, mut horiz_accuracy, mut vert_accuracy) = (0.0, 0.0, 0.0, 0.0);
+///     let updated = unsafe { get_position(&mut lat, &mut lon, &mut horiz_accuracy, &mut vert_accuracy) };
+///     updated.then_some((lat, lon))
+/// }
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LocationCallback {
+    pub start: retro_location_start_t,
+    pub stop: retro_location_stop_t,
+    pub get_position: retro_location_get_position_t,
+    pub set_interval: retro_location_set_interval_t,
+
+    pub initialized: retro_location_lifetime_status_t,
+    pub deinitialized: retro_location_lifetime_status_t,
+}
+
+pub type retro_usec_t = i64;
+
+/// Notifies a libretro core of time spent since last invocation
+/// of retro_run() in microseconds.
+///
+/// It will be called right before retro_run() every frame.
+/// The frontend can tamper with timing to support cases like
+/// fast-forward, slow-motion and framestepping.
+///
+/// In those scenarios the reference frame time value will be used.
+pub type retro_frame_time_callback_t = Option<unsafe extern "C" fn(usec: retro_usec_t)>;
+
+/// Passed to ENVIRONMENT_SET_FRAME_TIME_CALLBACK. Advancing the
+/// simulation by the real frame time:
+///
+/// ```
+/// use std::sync::atomic::{AtomicI64, Ordering};
+/// use libretro_sys::{retro_usec_t, FrameTimeCallback};
+///
+/// static FRAME_DELTA_USEC: AtomicI64 = AtomicI64::new(0);
+///
+/// unsafe extern "C" fn frame_time(usec: retro_usec_t) {
+///     FRAME_DELTA_USEC.store(usec, Ordering::Relaxed);
+/// }
+///
+/// let callback = FrameTimeCallback {
+///     callback: Some(frame_time),
+///     reference: 1_000_000 / 60,
+/// };
+///
+/// // In retro_run():
+/// let delta_seconds = FRAME_DELTA_USEC.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameTimeCallback {
+    pub callback: retro_frame_time_callback_t,
+
+    /// Represents the time of one frame. It is computed as
+    /// 1000000 / fps, but the implementation will resolve the
+    /// rounding to ensure that framestepping, etc is exact.
+    pub reference: retro_usec_t,
+}
+
+/// Notifies libretro that audio data should be written.
+pub type retro_audio_callback_t = Option<unsafe extern "C" fn()>;
+
+/// True: Audio driver in frontend is active, and callback is
+/// expected to be called regularily.
+/// False: Audio driver in frontend is paused or inactive.
+/// Audio callback will not be called until set_state has been
+/// called with true.
+/// Initial state is false (inactive).
+pub type retro_audio_set_state_callback_t = Option<unsafe extern "C" fn(enabled: bool)>;
+
+/// Callbacks for RETRO_ENVIRONMENT_SET_AUDIO_CALLBACK.
+/// The frontend may call `callback` from any thread, so the core's audio
+/// generation must be thread safe; [`AsyncAudio::trampoline`] runs a
+/// closure under a lock for this purpose:
+///
+/// ```
+/// use libretro_sys::{AsyncAudio, AudioCallback};
+///
+/// unsafe extern "C" fn set_state(_enabled: bool) {}
+///
+/// AsyncAudio::set(|| {
+///     // Mix the next block of samples and hand it to audio_sample_batch.
+/// });
+/// let callback = AudioCallback {
+///     callback: Some(AsyncAudio::trampoline),
+///     set_state: Some(set_state),
+/// };
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AudioCallback {
+    pub callback: retro_audio_callback_t,
+    pub set_state: retro_audio_set_state_callback_t,
+}
+
+/// Notifies a libretro core of the current occupancy
+/// level of the frontend audio buffer.
+///
+/// - active: 'true' if audio buffer is currently
+///           in use. Will be 'false' if audio is
+///           disabled in the frontend
+///
+/// - occupancy: Given as a value in the range [0,100],
+///              corresponding to the occupancy percentage
+///              of the audio buffer
+///
+/// - underrun_likely: 'true' if the frontend expects an
+///                    audio buffer underrun during the
+///                    next frame (indicates that a core
+///                    should attempt frame skipping)
+///
+/// It will be called right before retro_run() every frame.
+pub type retro_audio_buffer_status_callback_t =
+    Option<unsafe extern "C" fn(active: bool, occupancy: libc::c_uint, underrun_likely: bool)>;
+
+/// Passed to ENVIRONMENT_SET_AUDIO_BUFFER_STATUS_CALLBACK. Skipping
+/// video frames while the audio buffer runs low:
+///
+/// ```
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use libretro_sys::AudioBufferStatusCallback;
+///
+/// static SKIP_FRAME: AtomicBool = AtomicBool::new(false);
+///
+/// unsafe extern "C" fn buffer_status(active: bool, occupancy: std::ffi::c_uint, underrun_likely: bool) {
+///     SKIP_FRAME.store(active && (underrun_likely || occupancy < 25), Ordering::Relaxed);
+/// }
+///
+/// let callback = AudioBufferStatusCallback {
+///     callback: Some(buffer_status),
+/// };
+///
+/// // In retro_run(), emulate the frame but only render it when not skipping:
+/// let render = !SKIP_FRAME.load(Ordering::Relaxed);
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AudioBufferStatusCallback {
+    pub callback: retro_audio_buffer_status_callback_t,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Message {
+    /// Message to be displayed.
+    pub msg: *const libc::c_char,
+
+    /// Duration in frames of message.
+    pub frames: libc::c_uint,
+}
+
+/// Defines the type of a message sent via ENVIRONMENT_SET_MESSAGE_EXT.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageTarget {
+    All = 0,
+    Osd,
+    Log,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageType {
+    Notification = 0,
+    NotificationAlt,
+    Status,
+    Progress,
+}
+
+/// Passed to ENVIRONMENT_SET_MESSAGE_EXT. Reporting content loading
+/// progress on the OSD:
+///
+/// ```
+/// use std::ffi::c_uint;
+/// use libretro_sys::{LogLevel, MessageExt, MessageTarget, MessageType};
+///
+/// let message = MessageExt {
+///     msg: c"Loading disc image: 40%".as_ptr(),
+///     duration: 1000,
+///     priority: 1,
+///     level: LogLevel::Info as c_uint,
+///     target: MessageTarget::Osd as c_uint,
+///     type_: MessageType::Progress as c_uint,
+///     progress: 40,
+/// };
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct MessageExt {
+    /// Message string to be displayed/logged
+    pub msg: *const libc::c_char,
+
+    /// Duration (in ms) of message when targeting the OSD
+    pub duration: libc::c_uint,
+
+    /// Message priority when targeting the OSD
+    /// > When multiple concurrent messages are sent to
+    ///   the frontend and the frontend does not have the
+    ///   capacity to display them all, messages with the
+    ///   *highest* priority value should be shown
+    /// > There is no upper limit to a message priority
+    ///   value (within the bounds of the unsigned data type)
+    /// > In the reference frontend (RetroArch), the same
+    ///   priority values are used for frontend-generated
+    ///   notifications, which are typically assigned values
+    ///   between 0 and 3 depending upon importance
+    pub priority: libc::c_uint,
+
+    /// Message logging level (info, warn, error, etc.), see [`LogLevel`].
+    pub level: libc::c_uint,
+
+    /// Message destination: OSD, logging interface or both,
+    /// see [`MessageTarget`].
+    pub target: libc::c_uint,
+
+    /// Message 'type' when targeting the OSD, see [`MessageType`].
+    /// > RETRO_MESSAGE_TYPE_NOTIFICATION: Specifies that a
+    ///   message should be handled in identical fashion to
+    ///   a standard frontend-generated notification
+    /// > RETRO_MESSAGE_TYPE_NOTIFICATION_ALT: Specifies that
+    ///   message is a notification that requires user attention
+    ///   or action, but that it should be displayed in a manner
+    ///   that differs from standard frontend-generated notifications.
+    ///   This would typically correspond to messages that should be
+    ///   displayed immediately (independently from any internal
+    ///   frontend message queue), and/or which should be visually
+    ///   distinguishable from frontend-generated notifications.
+    ///   For example, a core may wish to inform the user of
+    ///   information related to a disk-change event. It is
+    ///   expected that the frontend itself may provide a
+    ///   notification in this case; if the core sends a
+    ///   message of type RETRO_MESSAGE_TYPE_NOTIFICATION, an
+    ///   uncomfortable 'double-notification' may occur. A message
+    ///   of RETRO_MESSAGE_TYPE_NOTIFICATION_ALT should therefore
+    ///   be presented such that visual conflict with regular
+    ///   notifications does not occur
+    /// > RETRO_MESSAGE_TYPE_STATUS: Indicates that message
+    ///   is not a standard notification. This typically
+    ///   corresponds to 'status' indicators, such as a core's
+    ///   internal FPS, which are intended to be displayed
+    ///   either permanently while a core is running, or in
+    ///   a manner that does not suggest user attention or action
+    ///   is required. 'Status' type messages should therefore be
+    ///   displayed in a different on-screen location and in a manner
+    ///   easily distinguishable from both standard frontend-generated
+    ///   notifications and messages of type RETRO_MESSAGE_TYPE_NOTIFICATION_ALT
+    /// > RETRO_MESSAGE_TYPE_PROGRESS: Indicates that message reports
+    ///   the progress of an internal core task. For example, in cases
+    ///   where a core itself handles the loading of content from a file,
+    ///   this may correspond to the percentage of the file that has been
+    ///   read. Alternatively, an audio/video playback core may use a
+    ///   message of type RETRO_MESSAGE_TYPE_PROGRESS to display the current
+    ///   playback position as a percentage of the runtime. 'Progress' type
+    ///   messages should therefore be displayed as a literal progress bar,
+    ///   where:
+    ///   - 'retro_message_ext.msg' is the progress bar title/label
+    ///   - 'retro_message_ext.progress' determines the length of
+    ///     the progress bar
+    /// NOTE: Message type is a *hint*, and may be ignored
+    /// by the frontend. If a frontend lacks support for
+    /// displaying messages via alternate means than standard
+    /// frontend-generated notifications, it will treat *all*
+    /// messages as having the type RETRO_MESSAGE_TYPE_NOTIFICATION
+    pub type_: libc::c_uint,
+
+    /// Task progress when targeting the OSD and message is
+    /// of type RETRO_MESSAGE_TYPE_PROGRESS
+    /// > -1:    Unmetered/indeterminate
+    /// > 0-100: Current progress percentage
+    /// NOTE: Since message type is a hint, a frontend may ignore
+    /// progress values. Where relevant, a core should therefore
+    /// include progress percentage within the message string,
+    /// such that the message intent remains clear when displayed
+    /// as a standard frontend-generated notification
+    pub progress: i8,
+}
+
+impl MessageExt {
+    /// Sets `progress`, clamped to -1 (indeterminate) or 0-100 percent.
+    pub fn with_progress(mut self, progress: i32) -> Self {
+        self.progress = progress.clamp(-1, 100) as i8;
+        self
+    }
+
+    /// Whether a progress message shows a metered bar rather than the
+    /// -1 indeterminate sentinel.
+    pub fn has_progress_bar(&self) -> bool {
+        self.progress >= 0
+    }
+}
+
+/// Shows `text` as an OSD notification for `seconds`. Frontends with
+/// message interface version 1 or later get SET_MESSAGE_EXT; older ones
+/// get SET_MESSAGE with the duration converted to frames at `fps`.
+/// Interior NUL bytes in `text` are dropped. Returns the frontend's answer,
+/// or false when `cb` is NULL.
+///
+/// # Safety
+/// `cb` must be NULL or the environment callback given to the core.
+pub unsafe fn show_message(cb: retro_environment_t, text: &str, seconds: f32, fps: f64, level: LogLevel) -> bool {
+    let Some(cb) = cb else {
+        return false;
+    };
+    let msg = CString::new(text.replace('\0', "")).expect("interior NUL bytes are filtered out");
+    let mut version: libc::c_uint = 0;
+    if !cb(ENVIRONMENT_GET_MESSAGE_INTERFACE_VERSION, (&mut version as *mut libc::c_uint).cast()) {
+        version = 0;
+    }
+    if version >= 1 {
+        let mut message = MessageExt {
+            msg: msg.as_ptr(),
+            duration: (seconds * 1000.0).round() as libc::c_uint,
+            priority: 1,
+            level: level as libc::c_uint,
+            target: MessageTarget::All as libc::c_uint,
+            type_: MessageType::Notification as libc::c_uint,
+            progress: -1,
+        };
+        cb(ENVIRONMENT_SET_MESSAGE_EXT, (&mut message as *mut MessageExt).cast())
+    } else {
+        let mut message = Message { msg: msg.as_ptr(), frames: (f64::from(seconds) * fps).round() as libc::c_uint };
+        cb(ENVIRONMENT_SET_MESSAGE, (&mut message as *mut Message).cast())
+    }
+}
+
+/// Delivers `ext` according to its target. Log and All messages go to
+/// `log` when it is available, and Osd and All messages go to the
+/// frontend through SET_MESSAGE_EXT, retargeted to the OSD once logged
+/// so the frontend does not log them twice. Without `log`, the frontend
+/// receives `ext` unchanged and handles its logging itself. Unknown
+/// targets are treated as All. Returns true if anything accepted it.
+///
+/// # Safety
+/// `cb` must be NULL or the environment callback given to the core,
+/// `log` must have been filled in by ENVIRONMENT_GET_LOG_INTERFACE, and
+/// `ext.msg` must be a valid C string.
+pub unsafe fn route_message(cb: retro_environment_t, ext: &MessageExt, log: Option<&LogCallback>) -> bool {
+    let to_log = ext.target != MessageTarget::Osd as libc::c_uint;
+    let to_osd = ext.target != MessageTarget::Log as libc::c_uint;
+    let mut message = *ext;
+    let mut delivered = false;
+    if let Some(log) = log.and_then(|log| log.log).filter(|_| to_log) {
+        log(ext.level, c"%s\n".as_ptr(), ext.msg);
+        if !to_osd {
+            return true;
+        }
+        message.target = MessageTarget::Osd as libc::c_uint;
+        delivered = true;
+    }
+    if let Some(cb) = cb {
+        delivered |= cb(ENVIRONMENT_SET_MESSAGE_EXT, (&mut message as *mut MessageExt).cast());
+    }
+    delivered
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SubsystemMemoryInfo {
+    /// The extension associated with a memory type, e.g. "psram".
+    pub extension: *const libc::c_char,
+
+    /// The memory type for retro_get_memory(). This should be at
+    /// least 0x100 to avoid conflict with standardized
+    /// libretro memory types.
+    pub type_: libc::c_uint,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SubsystemRomInfo {
+    /// Describes what the content is (SGB BIOS, GB ROM, etc).
+    pub desc: *const libc::c_char,
+
+    /// Same definition as retro_get_system_info().
+    pub valid_extensions: *const libc::c_char,
+
+    /// Same definition as retro_get_system_info().
+    pub need_fullpath: bool,
+
+    /// Same definition as retro_get_system_info().
+    pub block_extract: bool,
+
+    /// This is set if the content is required to load a game.
+    /// If this is set to false, a zeroed-out retro_game_info can be passed.
+    pub required: bool,
+
+    /// Content can have multiple associated persistent
+    /// memory types (retro_get_memory()).
+    pub memory: *const SubsystemMemoryInfo,
+    pub num_memory: libc::c_uint,
+}
+
+/// An array of these, terminated by a zeroed entry, is passed to
+/// ENVIRONMENT_SET_SUBSYSTEM_INFO. Super GameBoy content for a Game Boy
+/// core:
+///
+/// ```
+/// use libretro_sys::*;
+///
+/// let gb_memory = [SubsystemMemoryInfo {
+///     extension: c"srm".as_ptr(),
+///     type_: 0x101,
+/// }];
+/// let roms = [
+///     SubsystemRomInfo {
+///         desc: c"GameBoy ROM".as_ptr(),
+///         valid_extensions: c"gb|gbc".as_ptr(),
+///         need_fullpath: false,
+///         block_extract: false,
+///         required: true,
+///         memory: gb_memory.as_ptr(),
+///         num_memory: gb_memory.len() as _,
+///     },
+///     SubsystemRomInfo {
+///         desc: c"Super GameBoy BIOS".as_ptr(),
+///         valid_extensions: c"sfc|smc".as_ptr(),
+///         need_fullpath: false,
+///         block_extract: false,
+///         required: true,
+///         memory: std::ptr::null(),
+///         num_memory: 0,
+///     },
+/// ];
+/// let subsystems = [
+///     SubsystemInfo {
+///         desc: c"Super GameBoy".as_ptr(),
+///         ident: c"sgb".as_ptr(),
+///         roms: roms.as_ptr(),
+///         num_roms: roms.len() as _,
+///         id: 0x101,
+///     },
+///     SubsystemInfo {
+///         desc: std::ptr::null(),
+///         ident: std::ptr::null(),
+///         roms: std::ptr::null(),
+///         num_roms: 0,
+///         id: 0,
+///     },
+/// ];
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SubsystemInfo {
+    /// Human-readable string of the subsystem type, e.g. "Super GameBoy"
+    pub desc: *const libc::c_char,
+
+    /// A computer friendly short string identifier for the subsystem type.
+    /// This name must be [a-z].
+    /// E.g. if desc is "Super GameBoy", this can be "sgb".
+    /// This identifier can be used for command-line interfaces, etc.
+    pub ident: *const libc::c_char,
+
+    /// Infos for each content file. The first entry is assumed to be the
+    /// "most significant" content for frontend purposes.
+    /// E.g. with Super GameBoy, the first content should be the GameBoy ROM,
+    /// as it is the most "significant" content to a user.
+    /// If a frontend creates new file paths based on the content used
+    /// (e.g. savestates), it should use the path for the first ROM to do so.
+    pub roms: *const SubsystemRomInfo,
+
+    /// Number of content files associated with a subsystem.
+    pub num_roms: libc::c_uint,
+
+    /// The type passed to retro_load_game_special().
+    pub id: libc::c_uint,
+}
+
+/// Rewrites a path for the VFS interface, which expects forward slashes
+/// on every platform: backslashes become '/' and runs of separators are
+/// collapsed into one.
+pub fn normalize_vfs_path(path: &str) -> CString {
+    let mut normalized = String::with_capacity(path.len());
+    for c in path.chars().filter(|&c| c != '\0') {
+        let c = if c == '\\' { '/' } else { c };
+        if c == '/' && normalized.ends_with('/') {
+            continue;
+        }
+        normalized.push(c);
+    }
+    CString::new(normalized).expect("interior NUL bytes are filtered out")
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ControllerDescription {
+    /// Human-readable description of the controller. Even if using a generic
+    /// input device type, this can be set to the particular device type the
+    /// core uses.
+    pub desc: *const libc::c_char,
+
+    /// Device type passed to retro_set_controller_port_device(). If the device
+    /// type is a sub-class of a generic input device type, use the
+    /// RETRO_DEVICE_SUBCLASS macro to create an ID.
+    ///
+    /// E.g. RETRO_DEVICE_SUBCLASS(RETRO_DEVICE_JOYPAD, 1).
+    pub id: libc::c_uint,
+}
+
+/// One entry per port, terminated by [`ControllerInfo::default`], is
+/// passed to ENVIRONMENT_SET_CONTROLLER_INFO. Offering two light guns
+/// on port 0:
+///
+/// ```
+/// use libretro_sys::*;
+///
+/// let port0 = [
+///     ControllerDescription {
+///         desc: c"Super Scope".as_ptr(),
+///         id: device_subclass(DEVICE_LIGHTGUN, 0),
+///     },
+///     ControllerDescription {
+///         desc: c"Justifier".as_ptr(),
+///         id: device_subclass(DEVICE_LIGHTGUN, 1),
+///     },
+/// ];
+/// let ports = [
+///     ControllerInfo {
+///         types: port0.as_ptr(),
+///         num_types: port0.len() as _,
+///     },
+///     ControllerInfo::default(),
+/// ];
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ControllerInfo {
+    pub types: *const ControllerDescription,
+    pub num_types: libc::c_uint,
+}
+
+impl Default for ControllerInfo {
+    /// The { NULL, 0 } terminator of a SET_CONTROLLER_INFO array.
+    fn default() -> Self {
+        Self {
+            types: std::ptr::null(),
+            num_types: 0,
+        }
+    }
+}
+
+/// Number of ports described by a SET_CONTROLLER_INFO array: the entries
+/// before the first one with NULL `types`, or all of `infos` if there is
+/// no terminator.
+pub fn controller_port_count(infos: &[ControllerInfo]) -> usize {
+    infos.iter().take_while(|info| !info.types.is_null()).count()
+}
+
+/// Whether `device` is one of the types a SET_CONTROLLER_INFO array
+/// offers on `port`. A subclass is also accepted when the port offers
+/// its base device. False for ports past the terminator.
+///
+/// # Safety
+/// Each entry before the terminator must point to `num_types`
+/// controller descriptions.
+pub unsafe fn validate_port_device(infos: &[ControllerInfo], port: u32, device: libc::c_uint) -> bool {
+    let Some(info) = infos[..controller_port_count(infos)].get(port as usize) else {
+        return false;
+    };
+    let base = device & DEVICE_MASK;
+    std::slice::from_raw_parts(info.types, info.num_types as usize)
+        .iter()
+        .any(|desc| desc.id == device || desc.id == base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn descriptor(port: libc::c_uint, device: libc::c_uint, id: libc::c_uint, description: &CStr) -> InputDescriptor {
+        InputDescriptor {
+            port,
+            device,
+            index: 0,
+            id,
+            description: description.as_ptr(),
+        }
+    }
+
+    const TERMINATOR: InputDescriptor = InputDescriptor {
+        port: 0,
+        device: 0,
+        index: 0,
+        id: 0,
+        description: std::ptr::null(),
+    };
+
+    #[test]
+    fn group_descriptors_by_button_filters_port() {
+        let descs = [
+            descriptor(0, DEVICE_JOYPAD, DEVICE_ID_JOYPAD_B, c"Jump"),
+            descriptor(1, DEVICE_JOYPAD, DEVICE_ID_JOYPAD_B, c"P2 Jump"),
+            descriptor(0, DEVICE_JOYPAD, DEVICE_ID_JOYPAD_START, c"Pause"),
+            descriptor(0, DEVICE_ANALOG, DEVICE_ID_ANALOG_X, c"Move"),
+        ];
+        let grouped = unsafe { group_descriptors_by_button(&descs, 0) };
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(grouped[&DEVICE_ID_JOYPAD_B], c"Jump");
+        assert_eq!(grouped[&DEVICE_ID_JOYPAD_START], c"Pause");
+    }
+
+    #[test]
+    fn group_descriptors_by_button_stops_at_terminator() {
+        let descs = [
+            descriptor(0, DEVICE_JOYPAD, DEVICE_ID_JOYPAD_A, c"Fire"),
+            TERMINATOR,
+            descriptor(0, DEVICE_JOYPAD, DEVICE_ID_JOYPAD_X, c"Ignored"),
+        ];
+        let grouped = unsafe { group_descriptors_by_button(&descs, 0) };
+        assert_eq!(grouped.keys().copied().collect::<Vec<_>>(), [DEVICE_ID_JOYPAD_A]);
+    }
+
+    #[test]
+    fn group_descriptors_by_button_includes_joypad_subclasses() {
+        let descs = [
+            descriptor(0, device_subclass(DEVICE_JOYPAD, 0), DEVICE_ID_JOYPAD_Y, c"Turbo"),
+            descriptor(0, device_subclass(DEVICE_MOUSE, 0), DEVICE_ID_MOUSE_LEFT, c"Click"),
+            TERMINATOR,
+        ];
+        let grouped = unsafe { group_descriptors_by_button(&descs, 0) };
+        assert_eq!(grouped.len(), 1);
+        assert_eq!(grouped[&DEVICE_ID_JOYPAD_Y], c"Turbo");
+    }
+
+    #[test]
+    fn normalize_extensions_lowercases_and_strips_dots() {
+        let exts = normalize_extensions(&[".SFC", "smc", " .Fig ", "", ".sfc", "SwC"]);
+        assert_eq!(exts.as_c_str(), c"sfc|smc|fig|swc");
+    }
+
+    #[test]
+    fn lightgun_state_reads_port() {
+        let state = LightgunState::read(1, |port, device, index, id| {
+            assert_eq!((port, device, index), (1, DEVICE_LIGHTGUN, 0));
+            match id {
+                DEVICE_ID_LIGHTGUN_SCREEN_X => -1200,
+                DEVICE_ID_LIGHTGUN_SCREEN_Y => 3400,
+                DEVICE_ID_LIGHTGUN_TRIGGER | DEVICE_ID_LIGHTGUN_START => 1,
+                _ => 0,
+            }
+        });
+        assert_eq!(state.screen_pos(), Some((-1200, 3400)));
+        assert!(state.trigger());
+        assert!(state.start());
+        assert!(!state.reload());
+        assert!(!state.offscreen());
+    }
+
+    #[test]
+    fn lightgun_state_offscreen_has_no_position() {
+        let state = LightgunState::read(0, |_, _, _, id| match id {
+            DEVICE_ID_LIGHTGUN_IS_OFFSCREEN | DEVICE_ID_LIGHTGUN_RELOAD => 1,
+            DEVICE_ID_LIGHTGUN_SCREEN_X => 0x7fff,
+            _ => 0,
+        });
+        assert!(state.offscreen());
+        assert!(state.reload());
+        assert_eq!(state.screen_pos(), None);
+    }
+
+    #[test]
+    fn ticks_elapsed_wraps_around() {
+        assert_eq!(ticks_elapsed(100, 250), 150);
+        assert_eq!(ticks_elapsed(u64::MAX - 9, 5), 15);
+        assert_eq!(ticks_elapsed(42, 42), 0);
+    }
+
+    #[test]
+    fn overlay_hints_for_retropad_preset() {
+        let descs = [
+            descriptor(0, DEVICE_JOYPAD, DEVICE_ID_JOYPAD_LEFT, c"D-Pad Left"),
+            descriptor(0, DEVICE_JOYPAD, DEVICE_ID_JOYPAD_B, c"B"),
+            descriptor(0, DEVICE_JOYPAD, DEVICE_ID_JOYPAD_A, c"A"),
+            descriptor(0, DEVICE_JOYPAD, DEVICE_ID_JOYPAD_SELECT, c"Select"),
+            descriptor(0, DEVICE_JOYPAD, DEVICE_ID_JOYPAD_START, c"Start"),
+            descriptor(1, DEVICE_JOYPAD, DEVICE_ID_JOYPAD_A, c"P2 A"),
+            TERMINATOR,
+        ];
+        let hints = unsafe { overlay_hints(&descs, 0) };
+        assert_eq!(
+            hints,
+            [
+                (DEVICE_ID_JOYPAD_B, "B".to_string()),
+                (DEVICE_ID_JOYPAD_SELECT, "Select".to_string()),
+                (DEVICE_ID_JOYPAD_START, "Start".to_string()),
+                (DEVICE_ID_JOYPAD_LEFT, "D-Pad Left".to_string()),
+                (DEVICE_ID_JOYPAD_A, "A".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn async_audio_trampoline_runs_closure_under_lock() {
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::TryLockError;
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        AsyncAudio::set(|| {
+            assert!(matches!(ASYNC_AUDIO.try_lock(), Err(TryLockError::WouldBlock)));
+            CALLS.fetch_add(1, Ordering::SeqCst);
+        });
+        AsyncAudio::trampoline();
+        AsyncAudio::trampoline();
+        assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+
+        AsyncAudio::clear();
+        AsyncAudio::trampoline();
+        assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn pixel_format_values() {
+        assert_eq!(PixelFormat::Format0RGB1555 as libc::c_uint, 0);
+        assert_eq!(PixelFormat::FormatXRGB8888 as libc::c_uint, 1);
+        assert_eq!(PixelFormat::FormatRGB565 as libc::c_uint, 2);
+        assert_eq!(PixelFormat::Unknown as libc::c_uint, i32::MAX as libc::c_uint);
+        assert_eq!(std::mem::size_of::<PixelFormat>(), std::mem::size_of::<libc::c_int>());
+    }
+
+    #[test]
+    fn pixel_format_round_trip() {
+        for format in [PixelFormat::Format0RGB1555, PixelFormat::FormatXRGB8888, PixelFormat::FormatRGB565] {
+            assert_eq!(PixelFormat::try_from(libc::c_uint::from(format)), Ok(format));
+        }
+        assert_eq!(PixelFormat::try_from(3), Err(3));
+        let unknown = libc::c_uint::from(PixelFormat::Unknown);
+        assert_eq!(PixelFormat::try_from(unknown), Err(unknown));
+    }
+
+    #[test]
+    fn pixel_format_bytes_per_pixel() {
+        assert_eq!(PixelFormat::Format0RGB1555.bytes_per_pixel(), 2);
+        assert_eq!(PixelFormat::FormatXRGB8888.bytes_per_pixel(), 4);
+        assert_eq!(PixelFormat::FormatRGB565.bytes_per_pixel(), 2);
+        assert_eq!(PixelFormat::Unknown.bytes_per_pixel(), 0);
+    }
+
+    #[test]
+    fn language_round_trip() {
+        for value in 0..Language::Last as libc::c_uint {
+            let language = Language::try_from(value).unwrap();
+            assert_eq!(libc::c_uint::from(language), value);
+        }
+        assert_eq!(Language::try_from(Language::Last as libc::c_uint), Err(35));
+        let dummy = libc::c_uint::from(Language::Dummy);
+        assert_eq!(Language::try_from(dummy), Err(dummy));
+    }
+
+    #[test]
+    fn language_as_bcp47() {
+        assert_eq!(Language::English.as_bcp47(), "en");
+        assert_eq!(Language::PortugueseBrazil.as_bcp47(), "pt-BR");
+        assert_eq!(Language::ChineseTraditional.as_bcp47(), "zh-Hant");
+        assert_eq!(Language::ChineseSimplified.as_bcp47(), "zh-Hans");
+        assert_eq!(Language::CatalanValencia.as_bcp47(), "ca-ES-valencia");
+        assert_eq!(Language::BritishEnglish.as_bcp47(), "en-GB");
+        assert_eq!(Language::Last.as_bcp47(), "und");
+        assert_eq!(Language::Dummy.as_bcp47(), "und");
+
+        let tags: BTreeSet<_> = LANGUAGES.iter().map(|language| language.as_bcp47()).collect();
+        assert_eq!(tags.len(), LANGUAGES.len());
+        assert!(!tags.contains("und"));
+    }
+
+    #[test]
+    fn key_from_code() {
+        assert_eq!(Key::from_code(13), Some(Key::Return));
+        assert_eq!(Key::from_code(32), Some(Key::Space));
+        assert_eq!(Key::from_code(97), Some(Key::A));
+        assert_eq!(Key::from_code(1), None);
+        assert_eq!(Key::from_code(Key::Last as libc::c_uint), None);
+        assert_eq!(Key::from_code(Key::Dummy as libc::c_uint), None);
+    }
+
+    #[test]
+    fn key_round_trip() {
+        let mut count = 0;
+        for code in 0..Key::Last as libc::c_uint {
+            if let Some(key) = Key::from_code(code) {
+                assert_eq!(libc::c_uint::from(key), code);
+                count += 1;
+            }
+        }
+        // Every variant of Key but Last and Dummy.
+        assert_eq!(count, 141);
+    }
+
+    #[test]
+    fn key_mod_bits() {
+        assert_eq!(KeyMod::NONE.bits(), 0x0000);
+        assert_eq!(KeyMod::SHIFT.bits(), 0x0001);
+        assert_eq!(KeyMod::CTRL.bits(), 0x0002);
+        assert_eq!(KeyMod::ALT.bits(), 0x0004);
+        assert_eq!(KeyMod::META.bits(), 0x0008);
+        assert_eq!(KeyMod::NUMLOCK.bits(), 0x0010);
+        assert_eq!(KeyMod::CAPSLOCK.bits(), 0x0020);
+        assert_eq!(KeyMod::SCROLLOCK.bits(), 0x0040);
+        assert_eq!(std::mem::size_of::<KeyMod>(), std::mem::size_of::<u16>());
+    }
+
+    #[test]
+    fn key_mod_contains() {
+        let mods = KeyMod::SHIFT | KeyMod::CTRL;
+        assert!(mods.contains(KeyMod::SHIFT));
+        assert!(mods.contains(KeyMod::SHIFT | KeyMod::CTRL));
+        assert!(!mods.contains(KeyMod::ALT));
+        assert!(!mods.contains(KeyMod::CTRL | KeyMod::ALT));
+        assert!(mods.contains(KeyMod::NONE));
+        assert_eq!(mods & KeyMod::CTRL, KeyMod::CTRL);
+        assert!((mods & KeyMod::META).is_empty());
+        assert_eq!(KeyMod::from(0x03), mods);
+    }
+
+    #[test]
+    fn mouse_state_reads_port() {
+        let state = MouseState::read(2, |port, device, index, id| {
+            assert_eq!((port, device, index), (2, DEVICE_MOUSE, 0));
+            match id {
+                DEVICE_ID_MOUSE_X => 5,
+                DEVICE_ID_MOUSE_Y => -7,
+                DEVICE_ID_MOUSE_LEFT | DEVICE_ID_MOUSE_BUTTON_5 => 1,
+                _ => 0,
+            }
+        });
+        assert_eq!(state.delta(), (5, -7));
+        assert!(state.left());
+        assert!(!state.right());
+        assert!(!state.middle());
+        assert!(!state.button4());
+        assert!(state.button5());
+        assert_eq!(state.wheel(), 0);
+    }
+
+    #[test]
+    fn mouse_state_wheel_sign() {
+        let wheel = |pressed: libc::c_uint| MouseState::read(0, |_, _, _, id| (id == pressed) as i16).wheel();
+        assert_eq!(wheel(DEVICE_ID_MOUSE_WHEELUP), 1);
+        assert_eq!(wheel(DEVICE_ID_MOUSE_WHEELDOWN), -1);
+    }
+
+    fn assert_layout<T>(size: usize, align: usize) {
+        assert_eq!((size_of::<T>(), align_of::<T>()), (size, align), "{}", std::any::type_name::<T>());
+    }
+
+    #[test]
+    fn struct_layouts() {
+        use std::mem::offset_of;
+
+        assert_layout::<KeyboardCallback>(size_of::<usize>(), align_of::<usize>());
+        assert!(KeyboardCallback::default().callback.is_none());
+
+        assert_eq!(RumbleEffect::Strong as libc::c_uint, 0);
+        assert_eq!(RumbleEffect::Weak as libc::c_uint, 1);
+        assert_eq!(size_of::<RumbleEffect>(), size_of::<libc::c_int>());
+        assert_eq!(size_of::<RumbleInterface>(), size_of::<usize>());
+        assert!(RumbleInterface::default().set_rumble_state.is_none());
+
+        assert_eq!(size_of::<SensorInterface>(), 2 * size_of::<usize>());
+        assert_eq!(offset_of!(SensorInterface, get_sensor_input), size_of::<usize>());
+        assert_eq!(size_of::<SensorAction>(), size_of::<libc::c_int>());
+        assert_eq!(SensorAction::AccelerometerEnable as libc::c_uint, 0);
+        assert_eq!(SensorAction::IlluminanceDisable as libc::c_uint, 5);
+        assert_eq!(SENSOR_ILLUMINANCE, 6);
+
+        assert_eq!(size_of::<LocationCallback>(), 6 * size_of::<usize>());
+        assert_eq!(offset_of!(LocationCallback, get_position), 2 * size_of::<usize>());
+        assert_eq!(offset_of!(LocationCallback, deinitialized), 5 * size_of::<usize>());
+
+        assert_eq!(size_of::<AudioCallback>(), 2 * size_of::<usize>());
+        assert_eq!(offset_of!(AudioCallback, set_state), size_of::<usize>());
+
+        assert_eq!(size_of::<AudioBufferStatusCallback>(), size_of::<usize>());
+        assert!(AudioBufferStatusCallback::default().callback.is_none());
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn struct_layouts_64bit() {
+        use std::mem::offset_of;
+
+        assert_layout::<GameGeometry>(20, 4);
+        assert_eq!(offset_of!(GameGeometry, aspect_ratio), 16);
+
+        assert_layout::<SystemTiming>(16, 8);
+
+        assert_layout::<SystemAvInfo>(40, 8);
+        assert_eq!(offset_of!(SystemAvInfo, timing), 24);
+
+        assert_layout::<GameInfo>(32, 8);
+        assert_eq!(offset_of!(GameInfo, data), 8);
+        assert_eq!(offset_of!(GameInfo, size), 16);
+        assert_eq!(offset_of!(GameInfo, meta), 24);
+
+        assert_layout::<GameInfoExt>(80, 8);
+        assert_eq!(offset_of!(GameInfoExt, size), 64);
+        assert_eq!(offset_of!(GameInfoExt, file_in_archive), 72);
+        assert_eq!(offset_of!(GameInfoExt, persistent_data), 73);
+
+        assert_eq!(size_of::<CoreOptionValue>(), 16);
+        assert_layout::<CoreOptionDefinition>(2080, 8);
+        assert_eq!(offset_of!(CoreOptionDefinition, values), 24);
+        assert_eq!(offset_of!(CoreOptionDefinition, default_value), 2072);
+
+        let defs = [CoreOptionDefinition::default(); 2];
+        let stride = &defs[1] as *const _ as usize - &defs[0] as *const _ as usize;
+        assert_eq!(stride, 2080);
+
+        assert_layout::<CoreOptionV2Category>(24, 8);
+
+        assert_layout::<CoreOptionV2Definition>(2104, 8);
+        assert_eq!(offset_of!(CoreOptionV2Definition, values), 48);
+        assert_eq!(offset_of!(CoreOptionV2Definition, default_value), 2096);
+
+        assert_eq!(size_of::<CoreOptionsV2>(), 16);
+        assert_eq!(offset_of!(CoreOptionsV2, definitions), 8);
+
+        assert_layout::<CoreOptionDisplay>(16, 8);
+        assert_eq!(offset_of!(CoreOptionDisplay, visible), 8);
+        assert_eq!(size_of::<CoreOptionsUpdateDisplayCallback>(), 8);
+
+        assert_layout::<DiskControlCallback>(56, 8);
+        assert_eq!(offset_of!(DiskControlCallback, get_image_index), 16);
+        assert_eq!(offset_of!(DiskControlCallback, add_image_index), 48);
+
+        assert_layout::<DiskControlExtCallback>(80, 8);
+        assert_eq!(offset_of!(DiskControlExtCallback, add_image_index), 48);
+        assert_eq!(offset_of!(DiskControlExtCallback, set_initial_image), 56);
+        assert_eq!(offset_of!(DiskControlExtCallback, get_image_label), 72);
+
+        assert_layout::<HwRenderCallback>(64, 8);
+        assert_eq!(offset_of!(HwRenderCallback, context_reset), 8);
+        assert_eq!(offset_of!(HwRenderCallback, depth), 32);
+        assert_eq!(offset_of!(HwRenderCallback, bottom_left_origin), 34);
+        assert_eq!(offset_of!(HwRenderCallback, version_major), 36);
+        assert_eq!(offset_of!(HwRenderCallback, cache_context), 44);
+        assert_eq!(offset_of!(HwRenderCallback, context_destroy), 48);
+        assert_eq!(offset_of!(HwRenderCallback, debug_context), 56);
+
+        assert_layout::<CameraCallback>(64, 8);
+        assert_eq!(offset_of!(CameraCallback, width), 8);
+        assert_eq!(offset_of!(CameraCallback, start), 16);
+        assert_eq!(offset_of!(CameraCallback, deinitialized), 56);
+        assert_eq!(size_of::<CameraBuffer>(), size_of::<libc::c_int>());
+
+        assert_layout::<PerfCounter>(40, 8);
+        assert_eq!(offset_of!(PerfCounter, call_cnt), 24);
+        assert_eq!(offset_of!(PerfCounter, registered), 32);
+
+        assert_eq!(size_of::<PerfCallback>(), 56);
+        assert_eq!(offset_of!(PerfCallback, get_perf_counter), 16);
+        assert_eq!(offset_of!(PerfCallback, perf_log), 48);
+
+        assert_layout::<FrameTimeCallback>(16, 8);
+        assert_eq!(offset_of!(FrameTimeCallback, reference), 8);
+
+        assert_eq!(size_of::<Message>(), 16);
+        assert_eq!(offset_of!(Message, frames), 8);
+
+        assert_layout::<MessageExt>(32, 8);
+        assert_eq!(offset_of!(MessageExt, duration), 8);
+        assert_eq!(offset_of!(MessageExt, target), 20);
+        assert_eq!(offset_of!(MessageExt, type_), 24);
+        assert_eq!(offset_of!(MessageExt, progress), 28);
+
+        assert_eq!(size_of::<MessageTarget>(), size_of::<libc::c_int>());
+        assert_eq!(MessageTarget::Log as libc::c_uint, 2);
+        assert_eq!(size_of::<MessageType>(), size_of::<libc::c_int>());
+        assert_eq!(MessageType::Progress as libc::c_uint, 3);
+
+        assert_eq!(size_of::<SubsystemMemoryInfo>(), 16);
+        assert_eq!(offset_of!(SubsystemMemoryInfo, type_), 8);
+
+        assert_layout::<SubsystemRomInfo>(40, 8);
+        assert_eq!(offset_of!(SubsystemRomInfo, need_fullpath), 16);
+        assert_eq!(offset_of!(SubsystemRomInfo, required), 18);
+        assert_eq!(offset_of!(SubsystemRomInfo, memory), 24);
+        assert_eq!(offset_of!(SubsystemRomInfo, num_memory), 32);
+
+        assert_eq!(size_of::<SubsystemInfo>(), 32);
+        assert_eq!(offset_of!(SubsystemInfo, num_roms), 24);
+        assert_eq!(offset_of!(SubsystemInfo, id), 28);
+
+        assert_eq!(size_of::<ControllerDescription>(), 16);
+        assert_eq!(offset_of!(ControllerDescription, id), 8);
+        assert_layout::<ControllerInfo>(16, 8);
+        assert_eq!(offset_of!(ControllerInfo, num_types), 8);
+    }
+
+    #[test]
+    fn game_info_from_buffer() {
+        let rom = [0x4e, 0x45, 0x53, 0x1a, 0x02];
+        let info = GameInfo::from_buffer(&rom);
+        assert_eq!(info.size, rom.len());
+        assert_eq!(info.data, rom.as_ptr().cast());
+        assert!(info.path.is_null());
+        assert!(info.meta.is_null());
+
+        let empty = GameInfo::from_buffer(&[]);
+        assert_eq!(empty.size, 0);
+    }
+
+    #[test]
+    fn game_info_from_path() {
+        let path = c"/roms/game.cue";
+        let info = GameInfo::from_path(path);
+        assert_eq!(info.path, path.as_ptr());
+        assert!(info.data.is_null());
+        assert_eq!(info.size, 0);
+        assert_eq!(info.as_ptr(), &*info as *const GameInfo);
+    }
+
+    #[test]
+    fn variable_parse_values() {
+        let value = c"Speed hack coprocessor X; false|true";
+        let var = Variable {
+            key: c"foo_option".as_ptr(),
+            value: value.as_ptr(),
+        };
+        let (desc, values) = unsafe { var.parse_values() }.unwrap();
+        assert_eq!(desc, "Speed hack coprocessor X");
+        assert_eq!(values, ["false", "true"]);
+
+        assert!(unsafe { Variable::default().parse_values() }.is_none());
+    }
+
+    #[test]
+    fn parse_variable_value_rejects_malformed() {
+        assert_eq!(parse_variable_value("Speed hack coprocessor X"), None);
+        assert_eq!(parse_variable_value("Speed hack coprocessor X;false|true"), None);
+        assert_eq!(parse_variable_value("Speed hack coprocessor X; "), None);
+        assert_eq!(parse_variable_value("Speed hack coprocessor X; ||"), None);
+        assert_eq!(parse_variable_value("Frameskip; 0"), Some(("Frameskip", vec!["0"])));
+    }
+
+    #[test]
+    fn game_info_from_buffer_with_path_and_meta() {
+        let rom = [0u8; 16];
+        let path = c"/roms/game.sfc";
+        let meta = c"crc=1234";
+        let info = GameInfo::from_buffer(&rom).with_path(path).with_meta(meta);
+        assert_eq!(info.path, path.as_ptr());
+        assert_eq!(info.data, rom.as_ptr().cast());
+        assert_eq!(info.size, 16);
+        assert_eq!(info.meta, meta.as_ptr());
+        assert_eq!(info.as_ptr(), &*info as *const GameInfo);
+    }
+
+    fn category(key: &CStr, desc: &CStr) -> CoreOptionV2Category {
+        CoreOptionV2Category {
+            key: key.as_ptr(),
+            desc: desc.as_ptr(),
+            info: std::ptr::null(),
+        }
+    }
+
+    #[test]
+    fn ordered_categories_keeps_declaration_order() {
+        let mut categories = [
+            category(c"video", c"Video"),
+            category(c"audio", c"Audio"),
+            category(c"input", c"Input"),
+            CoreOptionV2Category::default(),
+        ];
+        let opts = CoreOptionsV2 {
+            categories: categories.as_mut_ptr(),
+            definitions: std::ptr::null_mut(),
+        };
+        let keys: Vec<_> = unsafe { ordered_categories(&opts) }
+            .into_iter()
+            .map(|category| unsafe { CStr::from_ptr(category.key) })
+            .collect();
+        assert_eq!(keys, [c"video", c"audio", c"input"]);
+    }
+
+    #[test]
+    fn ordered_categories_null_array() {
+        let opts = CoreOptionsV2 {
+            categories: std::ptr::null_mut(),
+            definitions: std::ptr::null_mut(),
+        };
+        assert!(unsafe { ordered_categories(&opts) }.is_empty());
+    }
+
+    #[test]
+    fn core_options_intl_default_is_null() {
+        let intl = CoreOptionsIntl::default();
+        assert!(intl.us.is_null());
+        assert!(intl.local.is_null());
+
+        let intl = CoreOptionsV2Intl::default();
+        assert!(intl.us.is_null());
+        assert!(intl.local.is_null());
+    }
+
+    #[test]
+    fn core_options_v2_intl_without_local() {
+        let mut us = CoreOptionsV2 {
+            categories: std::ptr::null_mut(),
+            definitions: std::ptr::null_mut(),
+        };
+        let intl = CoreOptionsV2Intl {
+            us: &mut us,
+            local: std::ptr::null_mut(),
+        };
+        assert_eq!(intl.us as *const CoreOptionsV2, &us as *const CoreOptionsV2);
+        assert!(intl.local.is_null());
+        assert_eq!(std::mem::size_of::<CoreOptionsV2Intl>(), 2 * std::mem::size_of::<usize>());
+        assert_eq!(std::mem::size_of::<CoreOptionsIntl>(), 2 * std::mem::size_of::<usize>());
+    }
+
+    #[test]
+    fn update_display_callback_is_nullable() {
+        assert_eq!(
+            std::mem::size_of::<retro_core_options_update_display_callback_t>(),
+            std::mem::size_of::<usize>()
+        );
+        assert!(CoreOptionsUpdateDisplayCallback::default().callback.is_none());
+    }
+
+    #[test]
+    fn input_poller_allows_repeated_polls() {
+        let polls = std::cell::Cell::new(0);
+        let mut poller = InputPoller::new(|| polls.set(polls.get() + 1), |_, _, _, _| 1);
+        poller.begin_frame();
+        poller.poll();
+        poller.poll();
+        assert_eq!(poller.state(0, DEVICE_JOYPAD, 0, DEVICE_ID_JOYPAD_A), Some(1));
+        assert_eq!(polls.get(), 2);
+    }
+
+    #[test]
+    fn input_poller_resets_each_frame() {
+        let mut poller = InputPoller::new(|| {}, |_, _, _, _| 0);
+        assert!(!poller.polled());
+        poller.poll();
+        assert!(poller.polled());
+        poller.begin_frame();
+        assert!(!poller.polled());
+    }
+
+    #[test]
+    fn input_poller_reads_nothing_before_poll() {
+        let reads = std::cell::Cell::new(0);
+        let mut poller = InputPoller::new(
+            || {},
+            |_, _, _, _| {
+                reads.set(reads.get() + 1);
+                1
+            },
+        );
+        poller.begin_frame();
+        assert_eq!(poller.state(0, DEVICE_JOYPAD, 0, DEVICE_ID_JOYPAD_B), None);
+        assert!(poller.lightgun(0).is_none());
+        assert!(poller.mouse(0).is_none());
+        assert!(poller.keyboard(0, &[Key::Space as libc::c_uint]).is_none());
+        assert_eq!(reads.get(), 0);
+
+        poller.poll();
+        assert!(poller.mouse(0).is_some_and(|mouse| mouse.left()));
+        assert!(poller.keyboard(0, &[Key::Space as libc::c_uint]).is_some_and(|keys| keys.is_down(Key::Space as libc::c_uint)));
+        assert!(reads.get() > 0);
+    }
+
+    #[test]
+    fn environment_callback_is_ffi_safe() {
+        #[deny(improper_ctypes)]
+        extern "C" {
+            #[allow(dead_code)]
+            fn retro_set_environment(cb: retro_environment_t);
+        }
+
+        #[deny(improper_ctypes_definitions)]
+        unsafe extern "C" fn environment(cmd: libc::c_uint, data: *mut libc::c_void) -> bool {
+            *data.cast::<bool>() = true;
+            cmd == ENVIRONMENT_GET_CAN_DUPE
+        }
+
+        fn can_dupe(cb: retro_environment_t) -> bool {
+            let mut can_dupe = false;
+            cb.is_some_and(|cb| unsafe { cb(ENVIRONMENT_GET_CAN_DUPE, (&mut can_dupe as *mut bool).cast()) }) && can_dupe
+        }
+
+        assert_eq!(std::mem::size_of::<retro_environment_t>(), std::mem::size_of::<usize>());
+        assert!(can_dupe(Some(environment)));
+        assert!(!can_dupe(None));
+    }
+
+    #[test]
+    fn option_info_null_and_empty() {
+        let mut def = CoreOptionDefinition {
+            key: c"mycore_frameskip".as_ptr(),
+            ..Default::default()
+        };
+        assert_eq!(unsafe { def.info() }, None);
+        def.info = c"".as_ptr();
+        assert_eq!(unsafe { def.info() }, None);
+        def.info = c"Skip frames".as_ptr();
+        assert_eq!(unsafe { def.info() }, Some(c"Skip frames"));
+
+        let category = category(c"video", c"Video");
+        assert_eq!(unsafe { category.info() }, None);
+
+        let def = CoreOptionV2Definition::default();
+        assert_eq!(unsafe { def.info() }, None);
+        assert_eq!(unsafe { def.info_categorized() }, None);
+    }
+
+    #[test]
+    fn option_info_categorized_falls_back_to_info() {
+        let mut def = CoreOptionV2Definition {
+            info: c"Video > Skip frames".as_ptr(),
+            ..Default::default()
+        };
+        assert_eq!(unsafe { def.info_categorized() }, Some(c"Video > Skip frames"));
+        def.info_categorized = c"".as_ptr();
+        assert_eq!(unsafe { def.info_categorized() }, Some(c"Video > Skip frames"));
+        def.info_categorized = c"Skip frames".as_ptr();
+        assert_eq!(unsafe { def.info_categorized() }, Some(c"Skip frames"));
+    }
+
+    #[test]
+    fn frontend_callbacks_are_ffi_safe() {
+        #[deny(improper_ctypes)]
+        #[allow(dead_code)]
+        extern "C" {
+            fn retro_set_video_refresh(cb: retro_video_refresh_t);
+            fn retro_set_audio_sample(cb: retro_audio_sample_t);
+            fn retro_set_audio_sample_batch(cb: retro_audio_sample_batch_t);
+            fn retro_set_input_poll(cb: retro_input_poll_t);
+            fn retro_set_input_state(cb: retro_input_state_t);
+        }
+
+        let pointer = std::mem::size_of::<usize>();
+        assert_eq!(std::mem::size_of::<retro_video_refresh_t>(), pointer);
+        assert_eq!(std::mem::size_of::<retro_audio_sample_t>(), pointer);
+        assert_eq!(std::mem::size_of::<retro_audio_sample_batch_t>(), pointer);
+        assert_eq!(std::mem::size_of::<retro_input_poll_t>(), pointer);
+        assert_eq!(std::mem::size_of::<retro_input_state_t>(), pointer);
+    }
+
+    #[test]
+    fn temp_path_is_unique() {
+        let first = temp_path(c"saves/", "state");
+        let second = temp_path(c"saves/", "state");
+        assert_ne!(first, second);
+        let first = first.to_str().unwrap();
+        assert!(first.starts_with(&format!("saves/state-{}-", std::process::id())));
+        assert!(first.ends_with(".tmp"));
+    }
+
+    #[test]
+    fn temp_path_without_dir_is_relative() {
+        let path = temp_path(c"", "state");
+        let path = path.to_str().unwrap();
+        assert!(path.starts_with("state-"), "{}", path);
+
+        let path = temp_path(c"/", "state");
+        assert!(path.to_str().unwrap().starts_with("/state-"));
+    }
+
+    /// Definitions for the entry points declared under `link-core`, so the
+    /// test binary links without a real core.
+    #[cfg(feature = "link-core")]
+    mod stub_core {
+        use super::*;
+
+        #[no_mangle]
+        extern "C" fn retro_set_environment(_cb: retro_environment_t) {}
+        #[no_mangle]
+        extern "C" fn retro_set_video_refresh(_cb: retro_video_refresh_t) {}
+        #[no_mangle]
+        extern "C" fn retro_set_audio_sample(_cb: retro_audio_sample_t) {}
+        #[no_mangle]
+        extern "C" fn retro_set_audio_sample_batch(_cb: retro_audio_sample_batch_t) {}
+        #[no_mangle]
+        extern "C" fn retro_set_input_poll(_cb: retro_input_poll_t) {}
+        #[no_mangle]
+        extern "C" fn retro_set_input_state(_cb: retro_input_state_t) {}
+        #[no_mangle]
+        extern "C" fn retro_init() {}
+        #[no_mangle]
+        extern "C" fn retro_deinit() {}
+        #[no_mangle]
+        extern "C" fn retro_api_version() -> libc::c_uint {
+            API_VERSION
+        }
+        #[no_mangle]
+        extern "C" fn retro_get_system_info(_info: *mut SystemInfo) {}
+        #[no_mangle]
+        extern "C" fn retro_get_system_av_info(_info: *mut SystemAvInfo) {}
+        #[no_mangle]
+        extern "C" fn retro_set_controller_port_device(_port: libc::c_uint, _device: libc::c_uint) {}
+        #[no_mangle]
+        extern "C" fn retro_reset() {}
+        #[no_mangle]
+        extern "C" fn retro_run() {}
+        #[no_mangle]
+        extern "C" fn retro_serialize_size() -> libc::size_t {
+            0
+        }
+        #[no_mangle]
+        extern "C" fn retro_serialize(_data: *mut libc::c_void, _size: libc::size_t) -> bool {
+            false
+        }
+        #[no_mangle]
+        extern "C" fn retro_unserialize(_data: *const libc::c_void, _size: libc::size_t) -> bool {
+            false
+        }
+        #[no_mangle]
+        extern "C" fn retro_cheat_reset() {}
+        #[no_mangle]
+        extern "C" fn retro_cheat_set(_index: libc::c_uint, _enabled: bool, _code: *const libc::c_char) {}
+        #[no_mangle]
+        extern "C" fn retro_load_game(_game: *const GameInfo) -> bool {
+            false
+        }
+        #[no_mangle]
+        extern "C" fn retro_load_game_special(
+            _game_type: libc::c_uint,
+            _info: *const GameInfo,
+            _num_info: libc::size_t,
+        ) -> bool {
+            false
+        }
+        #[no_mangle]
+        extern "C" fn retro_unload_game() {}
+        #[no_mangle]
+        extern "C" fn retro_get_region() -> libc::c_uint {
+            REGION_NTSC
+        }
+        #[no_mangle]
+        extern "C" fn retro_get_memory_data(_id: libc::c_uint) -> *mut libc::c_void {
+            std::ptr::null_mut()
+        }
+        #[no_mangle]
+        extern "C" fn retro_get_memory_size(_id: libc::c_uint) -> libc::size_t {
+            0
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "link-core")]
+    fn link_core_symbols() {
+        let entry_points: [*const (); 25] = [
+            super::retro_set_environment as *const (),
+            super::retro_set_video_refresh as *const (),
+            super::retro_set_audio_sample as *const (),
+            super::retro_set_audio_sample_batch as *const (),
+            super::retro_set_input_poll as *const (),
+            super::retro_set_input_state as *const (),
+            super::retro_init as *const (),
+            super::retro_deinit as *const (),
+            super::retro_api_version as *const (),
+            super::retro_get_system_info as *const (),
+            super::retro_get_system_av_info as *const (),
+            super::retro_set_controller_port_device as *const (),
+            super::retro_reset as *const (),
+            super::retro_run as *const (),
+            super::retro_serialize_size as *const (),
+            super::retro_serialize as *const (),
+            super::retro_unserialize as *const (),
+            super::retro_cheat_reset as *const (),
+            super::retro_cheat_set as *const (),
+            super::retro_load_game as *const (),
+            super::retro_load_game_special as *const (),
+            super::retro_unload_game as *const (),
+            super::retro_get_region as *const (),
+            super::retro_get_memory_data as *const (),
+            super::retro_get_memory_size as *const (),
+        ];
+        assert!(entry_points.iter().all(|entry_point| !entry_point.is_null()));
+        assert_eq!(unsafe { super::retro_api_version() }, API_VERSION);
+    }
+
+    #[test]
+    fn perf_clock_delta_ns_conversion() {
+        let clock = PerfClock::new(PerfCallback::default());
+        // 3 GHz counter: 3000 ticks are a microsecond.
+        assert_eq!(clock.delta_ns(1_000, 4_000, 3_000_000_000), 1_000);
+        // 1 MHz counter: every tick is a microsecond.
+        assert_eq!(clock.delta_ns(u64::MAX, 1, 1_000_000), 2_000);
+        assert_eq!(clock.delta_ns(0, 1_000, 0), 0);
+        assert_eq!(clock.delta_ns(0, u64::MAX, 1), u64::MAX);
+    }
+
+    #[test]
+    fn perf_clock_reads_frontend_counters() {
+        unsafe extern "C" fn get_perf_counter() -> retro_perf_tick_t {
+            42
+        }
+
+        assert_eq!(unsafe { PerfClock::new(PerfCallback::default()).ticks() }, None);
+        let clock = PerfClock::new(PerfCallback { get_perf_counter: Some(get_perf_counter), ..Default::default() });
+        assert_eq!(unsafe { clock.ticks() }, Some(42));
+        assert_eq!(unsafe { clock.time_usec() }, None);
+    }
+
+    #[test]
+    fn hw_context_type_values() {
+        assert_eq!(std::mem::size_of::<HwContextType>(), std::mem::size_of::<libc::c_int>());
+        assert_eq!(libc::c_uint::from(HwContextType::OpenGlEsVersion), 5);
+        assert_eq!(libc::c_uint::from(HwContextType::D3D9), 10);
+        for value in 0..=10 {
+            assert_eq!(HwContextType::try_from(value).map(libc::c_uint::from), Ok(value));
+        }
+        assert_eq!(HwContextType::try_from(11), Err(11));
+        let dummy = libc::c_uint::from(HwContextType::Dummy);
+        assert_eq!(HwContextType::try_from(dummy), Err(dummy));
+    }
+
+    #[test]
+    fn analog_to_dpad_diagonal() {
+        assert_eq!(analog_to_dpad(20000, -20000, 16384), (true, false, false, true));
+        assert_eq!(analog_to_dpad(-32768, 32767, 16384), (false, true, true, false));
+    }
+
+    #[test]
+    fn analog_to_dpad_dead_zone() {
+        assert_eq!(analog_to_dpad(16384, -16384, 16384), (false, false, false, false));
+        assert_eq!(analog_to_dpad(0, 0, 0), (false, false, false, false));
+        assert_eq!(analog_to_dpad(16385, 100, 16384), (false, false, false, true));
+    }
+
+    #[test]
+    fn hw_frame_buffer_valid_sentinel() {
+        assert_eq!(HW_FRAME_BUFFER_VALID, -1isize as *const libc::c_void);
+        assert!(!HW_FRAME_BUFFER_VALID.is_null());
+    }
+
+    fn option_v2(key: &CStr, values: &[&CStr], default: &CStr) -> CoreOptionV2Definition {
+        let mut def = CoreOptionV2Definition {
+            key: key.as_ptr(),
+            desc: key.as_ptr(),
+            default_value: default.as_ptr(),
+            ..Default::default()
+        };
+        for (slot, value) in def.values.iter_mut().zip(values) {
+            slot.value = value.as_ptr();
+        }
+        def
+    }
+
+    fn options_v2(definitions: &mut [CoreOptionV2Definition]) -> CoreOptionsV2 {
+        assert!(definitions.last().is_some_and(|def| def.key.is_null()));
+        CoreOptionsV2 {
+            categories: std::ptr::null_mut(),
+            definitions: definitions.as_mut_ptr(),
+        }
+    }
+
+    #[test]
+    fn option_store_set() {
+        let mut definitions = [
+            option_v2(c"mycore_frameskip", &[c"disabled", c"auto", c"manual"], c"disabled"),
+            CoreOptionV2Definition::default(),
+        ];
+        let opts = options_v2(&mut definitions);
+        let mut store = unsafe { OptionStore::from_options_v2(&opts) };
+        assert_eq!(store.get(c"mycore_frameskip"), Some(c"disabled"));
+
+        assert!(!store.set(c"mycore_frameskip", c"sometimes"));
+        assert_eq!(store.get(c"mycore_frameskip"), Some(c"disabled"));
+        assert!(!store.set(c"mycore_unknown", c"auto"));
+
+        assert!(store.set(c"mycore_frameskip", c"auto"));
+        assert_eq!(store.get(c"mycore_frameskip"), Some(c"auto"));
+        assert_eq!(
+            store.values(c"mycore_frameskip").unwrap().collect::<Vec<_>>(),
+            [c"disabled", c"auto", c"manual"]
+        );
+    }
+
+    #[test]
+    fn option_store_invalid_default_uses_first_value() {
+        let mut definitions = [
+            option_v2(c"mycore_region", &[c"auto", c"ntsc", c"pal"], c"secam"),
+            option_v2(c"mycore_empty", &[], c""),
+            CoreOptionV2Definition::default(),
+        ];
+        let opts = options_v2(&mut definitions);
+        let store = unsafe { OptionStore::from_options_v2(&opts) };
+        assert_eq!(store.get(c"mycore_region"), Some(c"auto"));
+        assert_eq!(store.keys().collect::<Vec<_>>(), [c"mycore_region"]);
+    }
+
+    #[test]
+    fn log_callback_null() {
+        let logging = LogCallback { log: None };
+        assert!(logging.log.is_none());
+        assert_eq!(std::mem::size_of::<LogCallback>(), std::mem::size_of::<usize>());
+        assert!(LogLevel::Debug < LogLevel::Error);
+        assert_eq!(LogLevel::Error as libc::c_uint, 3);
+    }
+
+    #[test]
+    fn hw_render_callback_new() {
+        let hw = HwRenderCallback::new(HwContextType::OpenGlEsVersion, 3, 1);
+        assert_eq!(hw.context_type, HwContextType::OpenGlEsVersion as libc::c_uint);
+        assert_eq!((hw.version_major, hw.version_minor), (3, 1));
+        assert!(hw.context_reset.is_none());
+
+        let hw = HwRenderCallback::new(HwContextType::OpenGlEs2, 0, 0);
+        assert_eq!(hw.version_major, 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "OpenGlEsVersion contexts require version_major")]
+    fn hw_render_callback_new_requires_gles_version() {
+        HwRenderCallback::new(HwContextType::OpenGlEsVersion, 0, 0);
+    }
+
+    #[test]
+    fn simd_flags() {
+        assert_eq!(SIMD_SSE, 0x1);
+        assert_eq!(SIMD_NEON, 0x20);
+        assert_eq!(SIMD_MMX, 0x100);
+        assert_eq!(SIMD_AVX2, 0x1000);
+        assert_eq!(SIMD_ASIMD, 0x20_0000);
+
+        let flags = [
+            SIMD_SSE, SIMD_SSE2, SIMD_VMX, SIMD_VMX128, SIMD_AVX, SIMD_NEON, SIMD_SSE3, SIMD_SSSE3, SIMD_MMX,
+            SIMD_MMXEXT, SIMD_SSE4, SIMD_SSE42, SIMD_AVX2, SIMD_VFPU, SIMD_PS, SIMD_AES, SIMD_VFPV3, SIMD_VFPV4,
+            SIMD_POPCNT, SIMD_MOVBE, SIMD_CMOV, SIMD_ASIMD,
+        ];
+        for (bit, flag) in flags.into_iter().enumerate() {
+            assert_eq!(flag, 1 << bit);
+        }
+    }
+
+    #[test]
+    fn keyboard_state_reads_requested_keys() {
+        let keys = [Key::A.into(), Key::Space.into(), Key::LShift.into()];
+        let state = KeyboardState::read(0, &keys, |port, device, index, id| {
+            assert_eq!((port, device, index), (0, DEVICE_KEYBOARD, 0));
+            (id == Key::A as libc::c_uint || id == Key::LShift as libc::c_uint || id == Key::Z as libc::c_uint) as i16
+        });
+        assert!(state.is_down(Key::A.into()));
+        assert!(state.is_down(Key::LShift.into()));
+        assert!(!state.is_down(Key::Space.into()));
+        // Not part of the read, even though the callback reports it down.
+        assert!(!state.is_down(Key::Z.into()));
+    }
+
+    #[test]
+    fn find_duplicate_keys_reports_each_once() {
+        let mut definitions = [
+            option_v2(c"mycore_frameskip", &[c"disabled", c"auto"], c"disabled"),
+            option_v2(c"mycore_region", &[c"ntsc", c"pal"], c"ntsc"),
+            option_v2(c"mycore_frameskip", &[c"0", c"1"], c"0"),
+            option_v2(c"mycore_frameskip", &[c"off"], c"off"),
+            CoreOptionV2Definition::default(),
+        ];
+        let opts = options_v2(&mut definitions);
+        assert_eq!(unsafe { find_duplicate_keys(&opts) }, [c"mycore_frameskip"]);
+
+        let mut definitions = [
+            option_v2(c"mycore_region", &[c"ntsc", c"pal"], c"ntsc"),
+            CoreOptionV2Definition::default(),
+        ];
+        let opts = options_v2(&mut definitions);
+        assert!(unsafe { find_duplicate_keys(&opts) }.is_empty());
+    }
+
+    unsafe extern "C" fn prefers_vulkan(cmd: libc::c_uint, data: *mut libc::c_void) -> bool {
+        if cmd != ENVIRONMENT_GET_PREFERRED_HW_RENDER {
+            return false;
+        }
+        *data.cast::<libc::c_uint>() = HwContextType::Vulkan.into();
+        true
+    }
+
+    unsafe extern "C" fn no_preference(_cmd: libc::c_uint, _data: *mut libc::c_void) -> bool {
+        false
+    }
+
+    #[test]
+    fn negotiate_context_preferred_supported() {
+        let supported = [HwContextType::OpenGlCore, HwContextType::Vulkan];
+        assert_eq!(
+            unsafe { negotiate_context(Some(prefers_vulkan), &supported) },
+            Some(HwContextType::Vulkan)
+        );
+    }
+
+    #[test]
+    fn negotiate_context_preferred_unsupported() {
+        let supported = [HwContextType::OpenGlCore, HwContextType::OpenGl];
+        assert_eq!(
+            unsafe { negotiate_context(Some(prefers_vulkan), &supported) },
+            Some(HwContextType::OpenGlCore)
+        );
+        assert_eq!(
+            unsafe { negotiate_context(Some(no_preference), &supported) },
+            Some(HwContextType::OpenGlCore)
+        );
+        assert_eq!(unsafe { negotiate_context(Some(prefers_vulkan), &[]) }, None);
+    }
+
+    #[test]
+    fn negotiate_context_without_callback() {
+        let supported = [HwContextType::Vulkan, HwContextType::D3D11];
+        assert_eq!(unsafe { negotiate_context(None, &supported) }, Some(HwContextType::Vulkan));
+    }
+
+    #[test]
+    fn analog_with_deadzone_boundary() {
+        assert_eq!(analog_with_deadzone(0, 0, 0.2), (0.0, 0.0));
+        // Exactly on the dead zone edge still reads as centered.
+        let edge = (0.2f32 * 32767.0) as i16;
+        assert_eq!(analog_with_deadzone(edge, 0, 0.2), (0.0, 0.0));
+        let (x, y) = analog_with_deadzone(edge + 100, 0, 0.2);
+        assert!(x > 0.0 && x < 0.01, "{}", x);
+        assert_eq!(y, 0.0);
+    }
+
+    #[test]
+    fn analog_with_deadzone_full_deflection() {
+        assert_eq!(analog_with_deadzone(32767, 0, 0.2), (1.0, 0.0));
+        assert_eq!(analog_with_deadzone(0, -32768, 0.2), (0.0, -1.0));
+        let (x, y) = analog_with_deadzone(32767, 32767, 0.2);
+        assert!((x - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+        assert!((y - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn normalize_vfs_path_windows_style() {
+        assert_eq!(
+            normalize_vfs_path(r"C:\Users\player\\saves\game.srm").as_c_str(),
+            c"C:/Users/player/saves/game.srm"
+        );
+        assert_eq!(normalize_vfs_path(r"roms\/snes//").as_c_str(), c"roms/snes/");
+        assert_eq!(normalize_vfs_path("/already/unix").as_c_str(), c"/already/unix");
+    }
+
+    fn av_info(max_width: libc::c_uint, max_height: libc::c_uint) -> SystemAvInfo {
+        SystemAvInfo {
+            geometry: GameGeometry {
+                base_width: 320,
+                base_height: 240,
+                max_width,
+                max_height,
+                aspect_ratio: 4.0 / 3.0,
+            },
+            timing: SystemTiming {
+                fps: 60.0,
+                sample_rate: 48000.0,
+            },
+        }
+    }
+
+    #[test]
+    fn framebuffer_size_bytes_uses_max_geometry() {
+        let info = av_info(640, 480);
+        assert_eq!(framebuffer_size_bytes(&info, PixelFormat::FormatXRGB8888), 1_228_800);
+        assert_eq!(framebuffer_size_bytes(&info, PixelFormat::FormatRGB565), 614_400);
+        assert_eq!(framebuffer_size_bytes(&info, PixelFormat::Unknown), 0);
+    }
+
+    #[test]
+    fn framebuffer_size_bytes_saturates() {
+        let info = av_info(libc::c_uint::MAX, libc::c_uint::MAX);
+        let expected = (libc::c_uint::MAX as usize)
+            .checked_mul(libc::c_uint::MAX as usize)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .unwrap_or(usize::MAX);
+        assert_eq!(framebuffer_size_bytes(&info, PixelFormat::FormatXRGB8888), expected);
+    }
+
+    #[test]
+    fn device_subclass_masks_to_base() {
+        assert_eq!(device_subclass(DEVICE_JOYPAD, 0), 0x101);
+        assert_eq!(device_subclass(DEVICE_JOYPAD, 1), 0x201);
+        for base in [DEVICE_JOYPAD, DEVICE_MOUSE, DEVICE_KEYBOARD, DEVICE_LIGHTGUN, DEVICE_ANALOG, DEVICE_POINTER] {
+            for id in 0..4 {
+                let subclass = device_subclass(base, id);
+                assert_eq!(subclass & DEVICE_MASK, base);
+                assert_ne!(subclass, base);
+            }
+        }
+        let last = (libc::c_uint::MAX >> DEVICE_TYPE_SHIFT) - 1;
+        assert_eq!(device_subclass(DEVICE_JOYPAD, last), 0xFFFF_FF01);
+        assert_eq!(device_subclass(DEVICE_JOYPAD, libc::c_uint::MAX), DEVICE_JOYPAD);
+    }
+
+    #[test]
+    fn hw_render_callback_should_cache_context() {
+        let mut hw = HwRenderCallback::new(HwContextType::OpenGlCore, 3, 3);
+        assert!(!hw.should_cache_context());
+        hw.cache_context = true;
+        assert!(hw.should_cache_context());
+    }
+
+    #[test]
+    fn camera_trampolines_dispatch_to_handler() {
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::Arc;
+
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        impl CameraHandler for Recorder {
+            fn on_raw_frame(&mut self, frame: CameraFrame<'_>) {
+                self.0.lock().unwrap().push(format!("raw {}x{} {:?}", frame.width, frame.height, frame.pixels));
+            }
+
+            fn on_gl_texture(&mut self, texture_id: libc::c_uint, texture_target: libc::c_uint, affine: *const libc::c_float) {
+                let scale = unsafe { *affine };
+                self.0.lock().unwrap().push(format!("gl {texture_id} {texture_target:#x} {scale}"));
+            }
+        }
+
+        static STARTS: AtomicUsize = AtomicUsize::new(0);
+        static STOPS: AtomicUsize = AtomicUsize::new(0);
+
+        unsafe extern "C" fn start() -> bool {
+            STARTS.fetch_add(1, Ordering::SeqCst);
+            true
+        }
+
+        unsafe extern "C" fn stop() {
+            STOPS.fetch_add(1, Ordering::SeqCst);
+        }
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        Camera::set(Recorder(log.clone()));
+
+        let mut cb = Camera::callback(1 << CameraBuffer::RawFramebuffer as u64, 2, 2);
+        assert!(cb.frame_opengl_texture.is_none());
+        assert!(!unsafe { Camera::start(&cb) });
+
+        cb.start = Some(start);
+        cb.stop = Some(stop);
+        assert!(unsafe { Camera::start(&cb) });
+        unsafe { Camera::stop(&cb) };
+        assert_eq!((STARTS.load(Ordering::SeqCst), STOPS.load(Ordering::SeqCst)), (1, 1));
+
+        // Two rows padded to three pixels each.
+        let pixels = [1u32, 2, 0, 3, 4];
+        unsafe { cb.frame_raw_framebuffer.unwrap()(pixels.as_ptr(), 2, 2, 12) };
+        unsafe { cb.frame_raw_framebuffer.unwrap()(std::ptr::null(), 2, 2, 12) };
+
+        let cb = Camera::callback(1 << CameraBuffer::OpenglTexture as u64, 0, 0);
+        let affine = [2.0f32, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+        unsafe { cb.frame_opengl_texture.unwrap()(7, 0x0DE1, affine.as_ptr()) };
+
+        Camera::clear();
+        Camera::gl_trampoline(8, 0x0DE1, affine.as_ptr());
+
+        assert_eq!(*log.lock().unwrap(), ["raw 2x2 [1, 2, 0, 3, 4]", "gl 7 0xde1 2"]);
+    }
+
+    #[test]
+    fn system_info_extensions_are_normalized() {
+        let mut info = SystemInfo::default();
+        assert!(unsafe { info.extensions() }.is_empty());
+
+        info.valid_extensions = c".SFC|smc||.Fig|sfc".as_ptr();
+        assert_eq!(unsafe { info.extensions() }, ["sfc", "smc", "fig"]);
+    }
+
+    #[test]
+    fn option_defaults_validate_and_fall_back() {
+        let mut missing = option_v2(c"mycore_region", &[c"auto", c"ntsc", c"pal"], c"");
+        missing.default_value = std::ptr::null();
+        let mut definitions = [
+            option_v2(c"mycore_frameskip", &[c"disabled", c"auto"], c"auto"),
+            missing,
+            option_v2(c"mycore_palette", &[c"default", c"mono"], c"sepia"),
+            option_v2(c"mycore_empty", &[], c"on"),
+            CoreOptionV2Definition::default(),
+        ];
+        let opts = options_v2(&mut definitions);
+        assert_eq!(
+            unsafe { option_defaults(&opts) },
+            [
+                (c"mycore_frameskip", c"auto"),
+                (c"mycore_region", c"auto"),
+                (c"mycore_palette", c"default"),
+            ]
+        );
+    }
+
+    #[test]
+    fn rumble_set_both_sets_each_motor() {
+        static CALLS: Mutex<Vec<(libc::c_uint, libc::c_uint, u16)>> = Mutex::new(Vec::new());
+
+        unsafe extern "C" fn set_rumble_state(port: libc::c_uint, effect: libc::c_uint, strength: u16) -> bool {
+            CALLS.lock().unwrap().push((port, effect, strength));
+            effect == RumbleEffect::Strong as libc::c_uint
+        }
+
+        assert!(!unsafe { RumbleInterface::default().set_both(0, 1, 1) });
+
+        let rumble = RumbleInterface { set_rumble_state: Some(set_rumble_state) };
+        assert!(!unsafe { rumble.set_both(1, 0xffff, 0x8000) });
+        assert_eq!(
+            *CALLS.lock().unwrap(),
+            [
+                (1, RumbleEffect::Strong as libc::c_uint, 0xffff),
+                (1, RumbleEffect::Weak as libc::c_uint, 0x8000),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "log")]
+    fn log_level_mapping_round_trips() {
+        let pairs = [
+            (LogLevel::Debug, log::Level::Debug),
+            (LogLevel::Info, log::Level::Info),
+            (LogLevel::Warn, log::Level::Warn),
+            (LogLevel::Error, log::Level::Error),
+        ];
+        for (level, mapped) in pairs {
+            assert_eq!(to_log_level(level), mapped);
+            assert_eq!(from_log_level(mapped), level);
+        }
+        assert_eq!(from_log_level(log::Level::Trace), LogLevel::Debug);
+    }
+
+    #[test]
+    fn visibility_state_keeps_hidden_options_across_resubmission() {
+        static SHOWN: Mutex<Vec<(CString, bool)>> = Mutex::new(Vec::new());
+
+        unsafe extern "C" fn environment(cmd: libc::c_uint, data: *mut libc::c_void) -> bool {
+            assert_eq!(cmd, ENVIRONMENT_SET_CORE_OPTIONS_DISPLAY);
+            let display = &*data.cast::<CoreOptionDisplay>();
+            SHOWN.lock().unwrap().push((CStr::from_ptr(display.key).to_owned(), display.visible));
+            true
+        }
+
+        let mut definitions = [
+            option_v2(c"mycore_frameskip", &[c"disabled", c"auto"], c"disabled"),
+            option_v2(c"mycore_frameskip_threshold", &[c"15", c"30"], c"30"),
+            CoreOptionV2Definition::default(),
+        ];
+        let mut state = unsafe { VisibilityState::from_options_v2(&options_v2(&mut definitions)) };
+        assert_eq!(state.is_visible(c"mycore_frameskip_threshold"), Some(true));
+        assert!(state.set_visible(c"mycore_frameskip_threshold", false));
+        assert!(!state.set_visible(c"mycore_unknown", false));
+        assert!(unsafe { state.flush(Some(environment)) });
+
+        // Resubmitting resets visibility in the frontend; the tracker keeps
+        // the hidden option hidden and picks up the new one as visible.
+        let mut definitions = [
+            option_v2(c"mycore_frameskip_threshold", &[c"15", c"30"], c"30"),
+            option_v2(c"mycore_palette", &[c"default", c"mono"], c"default"),
+            CoreOptionV2Definition::default(),
+        ];
+        unsafe { state.set_options(&options_v2(&mut definitions)) };
+        assert_eq!(state.is_visible(c"mycore_frameskip"), None);
+        SHOWN.lock().unwrap().clear();
+        assert!(unsafe { state.flush(Some(environment)) });
+        assert_eq!(
+            *SHOWN.lock().unwrap(),
+            [(c"mycore_frameskip_threshold".to_owned(), false), (c"mycore_palette".to_owned(), true)]
+        );
+        assert!(!unsafe { state.flush(None) });
+    }
+
+    #[test]
+    fn hw_render_callback_required_attachments() {
+        let cases = [
+            ((false, false), (false, false)),
+            ((true, false), (true, false)),
+            ((false, true), (false, false)),
+            ((true, true), (true, true)),
+        ];
+        for ((depth, stencil), attachments) in cases {
+            let hw = HwRenderCallback::new(HwContextType::OpenGlCore, 3, 3)
+                .depth_buffer(depth)
+                .stencil_buffer(stencil);
+            assert_eq!((hw.depth, hw.stencil), (depth, stencil));
+            assert_eq!(hw.required_attachments(), attachments);
+        }
+    }
+
+    #[test]
+    fn options_to_text_golden() {
+        let mut definitions = [
+            option_v2(c"mycore_frameskip", &[c"disabled", c"auto", c"manual"], c"auto"),
+            option_v2(c"mycore_palette", &[c"default", c"mono"], c"sepia"),
+            option_v2(c"mycore_empty", &[], c"on"),
+            CoreOptionV2Definition::default(),
+        ];
+        let opts = options_v2(&mut definitions);
+        assert_eq!(
+            unsafe { options_to_text(&opts) },
+            "# disabled|auto|manual\nmycore_frameskip = auto\n\n# default|mono\nmycore_palette = default\n"
+        );
+    }
+
+    #[test]
+    fn options_text_round_trips() {
+        let mut definitions = [
+            option_v2(c"mycore_frameskip", &[c"disabled", c"auto"], c"auto"),
+            option_v2(c"mycore_region", &[c"ntsc", c"pal"], c"pal"),
+            CoreOptionV2Definition::default(),
+        ];
+        let opts = options_v2(&mut definitions);
+        let text = unsafe { options_to_text(&opts) };
+        let parsed: Vec<_> = parse_options_text(&text)
+            .into_iter()
+            .map(|(key, value)| (CString::new(key).unwrap(), CString::new(value).unwrap()))
+            .collect();
+        let defaults: Vec<_> = unsafe { option_defaults(&opts) }
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), value.to_owned()))
+            .collect();
+        assert_eq!(parsed, defaults);
+        assert_eq!(parse_options_text("  # note\nbroken line\n a =  b \n"), [("a", "b")]);
+    }
+
+    #[test]
+    fn show_message_uses_interface_version() {
+        static SENT: Mutex<Vec<(libc::c_uint, String, libc::c_uint)>> = Mutex::new(Vec::new());
+
+        unsafe fn record(cmd: libc::c_uint, data: *mut libc::c_void, version: libc::c_uint) -> bool {
+            match cmd {
+                ENVIRONMENT_GET_MESSAGE_INTERFACE_VERSION => *data.cast::<libc::c_uint>() = version,
+                ENVIRONMENT_SET_MESSAGE_EXT => {
+                    let message = &*data.cast::<MessageExt>();
+                    assert_eq!(message.level, LogLevel::Warn as libc::c_uint);
+                    let text = CStr::from_ptr(message.msg).to_string_lossy().into_owned();
+                    SENT.lock().unwrap().push((cmd, text, message.duration));
+                }
+                ENVIRONMENT_SET_MESSAGE => {
+                    let message = &*data.cast::<Message>();
+                    let text = CStr::from_ptr(message.msg).to_string_lossy().into_owned();
+                    SENT.lock().unwrap().push((cmd, text, message.frames));
+                }
+                _ => return false,
+            }
+            true
+        }
+
+        unsafe extern "C" fn version_1(cmd: libc::c_uint, data: *mut libc::c_void) -> bool {
+            record(cmd, data, 1)
+        }
+
+        unsafe extern "C" fn version_0(cmd: libc::c_uint, data: *mut libc::c_void) -> bool {
+            record(cmd, data, 0)
+        }
+
+        assert!(unsafe { show_message(Some(version_1), "Disk 2\0 inserted", 2.5, 60.0, LogLevel::Warn) });
+        assert!(unsafe { show_message(Some(version_0), "Disk 2 inserted", 2.5, 60.0, LogLevel::Warn) });
+        assert!(!unsafe { show_message(None, "Disk 2 inserted", 2.5, 60.0, LogLevel::Warn) });
+        assert_eq!(
+            *SENT.lock().unwrap(),
+            [
+                (ENVIRONMENT_SET_MESSAGE_EXT, "Disk 2 inserted".to_owned(), 2500),
+                (ENVIRONMENT_SET_MESSAGE, "Disk 2 inserted".to_owned(), 150),
+            ]
+        );
+    }
+
+    #[test]
+    fn controller_port_count_stops_at_terminator() {
+        let pad = [ControllerDescription { desc: c"RetroPad".as_ptr(), id: DEVICE_JOYPAD }];
+        let port = ControllerInfo { types: pad.as_ptr(), num_types: 1 };
+        let infos = [port, port, port, ControllerInfo::default(), port];
+        assert_eq!(controller_port_count(&infos), 3);
+        assert_eq!(controller_port_count(&infos[..2]), 2);
+        assert_eq!(controller_port_count(&[ControllerInfo::default()]), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "crc")]
+    fn content_crc32_of_known_buffer() {
+        let data = b"123456789";
+        assert_eq!(unsafe { content_crc32(&GameInfo::from_buffer(data)) }, Some(0xCBF4_3926));
+        assert_eq!(unsafe { content_crc32(&GameInfo::from_path(c"/roms/game.sfc")) }, None);
+    }
+
+    #[test]
+    fn hw_context_type_is_gles() {
+        assert!(HwContextType::OpenGlEs2.is_gles());
+        assert!(HwContextType::OpenGlEs3.is_gles());
+        assert!(HwContextType::OpenGlEsVersion.is_gles());
+        assert!(!HwContextType::OpenGl.is_gles());
+        assert!(!HwContextType::OpenGlCore.is_gles());
+        assert!(!HwContextType::Vulkan.is_gles());
+    }
+
+    #[test]
+    fn submit_and_check_categories_reports_support() {
+        use std::sync::atomic::AtomicUsize;
+
+        static SUBMITTED: AtomicUsize = AtomicUsize::new(0);
+
+        unsafe fn frontend(cmd: libc::c_uint, data: *mut libc::c_void, version: libc::c_uint, categories: bool) -> bool {
+            match cmd {
+                ENVIRONMENT_GET_CORE_OPTIONS_VERSION => {
+                    *data.cast::<libc::c_uint>() = version;
+                    true
+                }
+                ENVIRONMENT_SET_CORE_OPTIONS_V2 => {
+                    SUBMITTED.fetch_add(1, Ordering::SeqCst);
+                    categories
+                }
+                _ => false,
+            }
+        }
+
+        unsafe extern "C" fn with_categories(cmd: libc::c_uint, data: *mut libc::c_void) -> bool {
+            frontend(cmd, data, 2, true)
+        }
+
+        unsafe extern "C" fn without_categories(cmd: libc::c_uint, data: *mut libc::c_void) -> bool {
+            frontend(cmd, data, 2, false)
+        }
+
+        unsafe extern "C" fn version_1(cmd: libc::c_uint, data: *mut libc::c_void) -> bool {
+            frontend(cmd, data, 1, true)
+        }
+
+        let mut categories = [category(c"video", c"Video"), category(c"", c"")];
+        categories[1].key = std::ptr::null();
+        let mut definitions = [
+            option_v2(c"mycore_frameskip", &[c"disabled", c"auto"], c"disabled"),
+            CoreOptionV2Definition::default(),
+        ];
+        let mut opts = options_v2(&mut definitions);
+        opts.categories = categories.as_mut_ptr();
+
+        assert_eq!(unsafe { submit_and_check_categories(Some(with_categories), &opts) }, (true, true));
+        assert_eq!(unsafe { submit_and_check_categories(Some(without_categories), &opts) }, (true, false));
+        assert_eq!(SUBMITTED.load(Ordering::SeqCst), 2);
+
+        assert_eq!(unsafe { submit_and_check_categories(Some(version_1), &opts) }, (false, false));
+        assert_eq!(unsafe { submit_and_check_categories(None, &opts) }, (false, false));
+        assert_eq!(SUBMITTED.load(Ordering::SeqCst), 2);
+
+        opts.categories = std::ptr::null_mut();
+        assert_eq!(unsafe { submit_and_check_categories(Some(with_categories), &opts) }, (true, false));
+    }
+
+    #[test]
+    fn system_av_info_accessors() {
+        let mut info = av_info(640, 480);
+        assert_eq!(info.geometry().max_width, 640);
+        assert_eq!(info.timing().fps, 60.0);
+
+        info.geometry_mut().base_width = 256;
+        info.timing_mut().sample_rate = 32040.0;
+        assert_eq!(info.geometry.base_width, 256);
+        assert_eq!(info.timing.sample_rate, 32040.0);
+    }
+
+    #[test]
+    fn option_value_count_stops_at_null() {
+        let def = option_v2(c"mycore_speed", &[c"50", c"100", c"150", c"200"], c"100");
+        assert_eq!(option_value_count(&def), 4);
+        assert_eq!(option_value_count(&CoreOptionV2Definition::default()), 0);
+
+        let mut full = CoreOptionV2Definition::default();
+        for slot in &mut full.values {
+            slot.value = c"on".as_ptr();
+        }
+        assert_eq!(option_value_count(&full), NUM_CORE_OPTION_VALUES_MAX);
+    }
+
+    fn message_ext(msg: &CStr, target: MessageTarget) -> MessageExt {
+        MessageExt {
+            msg: msg.as_ptr(),
+            duration: 1000,
+            priority: 1,
+            level: LogLevel::Warn as libc::c_uint,
+            target: target as libc::c_uint,
+            type_: MessageType::Notification as libc::c_uint,
+            progress: -1,
+        }
+    }
+
+    thread_local! {
+        static OSD_TARGETS: std::cell::RefCell<Vec<libc::c_uint>> = const { std::cell::RefCell::new(Vec::new()) };
+    }
+
+    unsafe extern "C" fn message_ext_environment(cmd: libc::c_uint, data: *mut libc::c_void) -> bool {
+        assert_eq!(cmd, ENVIRONMENT_SET_MESSAGE_EXT);
+        let target = (*data.cast::<MessageExt>()).target;
+        OSD_TARGETS.with_borrow_mut(|targets| targets.push(target));
+        true
+    }
+
+    #[test]
+    fn route_message_without_log_interface() {
+        let env = Some(message_ext_environment as unsafe extern "C" fn(libc::c_uint, *mut libc::c_void) -> bool);
+        for target in [MessageTarget::All, MessageTarget::Osd, MessageTarget::Log] {
+            assert!(unsafe { route_message(env, &message_ext(c"Saved", target), None) });
+        }
+        assert!(!unsafe { route_message(None, &message_ext(c"Saved", MessageTarget::Log), None) });
+        assert_eq!(
+            OSD_TARGETS.take(),
+            [MessageTarget::All as libc::c_uint, MessageTarget::Osd as libc::c_uint, MessageTarget::Log as libc::c_uint]
+        );
+    }
+
+    // Stable Rust cannot define variadic functions. On the x86-64 System V
+    // ABI a fixed-arity function taking the same integer and pointer
+    // arguments is call-compatible, so it stands in for the frontend logger.
+    #[test]
+    #[cfg(all(target_arch = "x86_64", unix))]
+    fn route_message_with_log_interface() {
+        static LOGGED: Mutex<Vec<(libc::c_uint, String)>> = Mutex::new(Vec::new());
+
+        unsafe extern "C" fn log(level: libc::c_uint, fmt: *const libc::c_char, msg: *const libc::c_char) {
+            assert_eq!(CStr::from_ptr(fmt), c"%s\n");
+            LOGGED.lock().unwrap().push((level, CStr::from_ptr(msg).to_string_lossy().into_owned()));
+        }
+
+        let logging = LogCallback {
+            log: Some(unsafe {
+                std::mem::transmute::<
+                    unsafe extern "C" fn(libc::c_uint, *const libc::c_char, *const libc::c_char),
+                    unsafe extern "C" fn(libc::c_uint, *const libc::c_char, ...),
+                >(log)
+            }),
+        };
+        let env = Some(message_ext_environment as unsafe extern "C" fn(libc::c_uint, *mut libc::c_void) -> bool);
+
+        assert!(unsafe { route_message(env, &message_ext(c"all", MessageTarget::All), Some(&logging)) });
+        assert!(unsafe { route_message(env, &message_ext(c"osd", MessageTarget::Osd), Some(&logging)) });
+        assert!(unsafe { route_message(None, &message_ext(c"log", MessageTarget::Log), Some(&logging)) });
+
+        let warn = LogLevel::Warn as libc::c_uint;
+        assert_eq!(*LOGGED.lock().unwrap(), [(warn, "all".to_owned()), (warn, "log".to_owned())]);
+        assert_eq!(OSD_TARGETS.take(), [MessageTarget::Osd as libc::c_uint, MessageTarget::Osd as libc::c_uint]);
+    }
+
+    #[test]
+    fn hw_render_callback_debug_context() {
+        let hw = HwRenderCallback::new(HwContextType::OpenGlCore, 4, 5);
+        assert!(!hw.wants_debug_context());
+
+        let hw = hw.debug(true);
+        assert!(hw.debug_context);
+        assert!(hw.wants_debug_context());
+        assert!(!hw.debug(false).wants_debug_context());
+    }
+
+    #[test]
+    fn hw_context_type_version_fields_meaningful() {
+        assert!(HwContextType::OpenGlCore.version_fields_meaningful());
+        assert!(HwContextType::OpenGlEsVersion.version_fields_meaningful());
+        assert!(!HwContextType::OpenGl.version_fields_meaningful());
+        assert!(!HwContextType::OpenGlEs3.version_fields_meaningful());
+        assert!(!HwContextType::Vulkan.version_fields_meaningful());
+    }
+
+    #[test]
+    fn variables_to_options_v2_converts_values_and_defaults() {
+        let vars = [
+            Variable { key: c"mycore_frameskip".as_ptr(), value: c"Frameskip; disabled|auto|manual".as_ptr() },
+            Variable { key: c"mycore_broken".as_ptr(), value: c"Broken".as_ptr() },
+            Variable { key: c"mycore_region".as_ptr(), value: c"Region; pal|ntsc".as_ptr() },
+            Variable::default(),
+        ];
+        let mut builder = unsafe { variables_to_options_v2(&vars) };
+        assert_eq!(builder.definitions().len(), 2);
+        assert_eq!(unsafe { CStr::from_ptr(builder.definitions()[1].desc) }, c"Region");
+
+        let opts = builder.as_options_v2();
+        assert_eq!(
+            unsafe { option_defaults(&opts) },
+            [(c"mycore_frameskip", c"disabled"), (c"mycore_region", c"pal")]
+        );
+        let store = unsafe { OptionStore::from_options_v2(&opts) };
+        assert_eq!(
+            store.values(c"mycore_frameskip").unwrap().collect::<Vec<_>>(),
+            [c"disabled", c"auto", c"manual"]
+        );
+        assert_eq!(store.values(c"mycore_region").unwrap().collect::<Vec<_>>(), [c"pal", c"ntsc"]);
+    }
+
+    #[test]
+    fn core_options_v2_builder_keeps_values_terminated() {
+        let values: Vec<String> = (0..200).map(|i| i.to_string()).collect();
+        let values: Vec<&str> = values.iter().map(String::as_str).collect();
+        let mut builder = CoreOptionsV2Builder::new();
+        builder.option("mycore_many", "Many", &values, "0");
+        assert_eq!(option_value_count(&builder.definitions()[0]), NUM_CORE_OPTION_VALUES_MAX - 1);
+        assert!(unsafe { find_duplicate_keys(&builder.as_options_v2()) }.is_empty());
+    }
+
+    #[test]
+    fn validate_port_device_checks_offered_types() {
+        let port0 = [
+            ControllerDescription { desc: c"RetroPad".as_ptr(), id: DEVICE_JOYPAD },
+            ControllerDescription { desc: c"Justifier".as_ptr(), id: device_subclass(DEVICE_LIGHTGUN, 1) },
+        ];
+        let infos = [
+            ControllerInfo { types: port0.as_ptr(), num_types: port0.len() as _ },
+            ControllerInfo::default(),
+        ];
+        unsafe {
+            assert!(validate_port_device(&infos, 0, device_subclass(DEVICE_LIGHTGUN, 1)));
+            assert!(validate_port_device(&infos, 0, device_subclass(DEVICE_JOYPAD, 2)));
+            assert!(!validate_port_device(&infos, 0, device_subclass(DEVICE_LIGHTGUN, 0)));
+            assert!(!validate_port_device(&infos, 0, DEVICE_MOUSE));
+            assert!(!validate_port_device(&infos, 1, DEVICE_JOYPAD));
+        }
+    }
+
+    #[test]
+    fn message_ext_progress_is_clamped() {
+        let message = message_ext(c"Loading", MessageTarget::Osd);
+        assert!(!message.has_progress_bar());
+
+        let message = message.with_progress(40);
+        assert_eq!(message.progress, 40);
+        assert!(message.has_progress_bar());
+        assert!(message.with_progress(0).has_progress_bar());
+
+        assert_eq!(message.with_progress(250).progress, 100);
+        assert_eq!(message.with_progress(-1).progress, -1);
+        assert_eq!(message.with_progress(-90).progress, -1);
+        assert!(!message.with_progress(-90).has_progress_bar());
+    }
+}
@@ -0,0 +1,340 @@
+use std::ffi::CStr;
+use std::fmt;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::{fs, io, process};
+
+use libloading::Library;
+
+use crate::{
+    retro_audio_sample_batch_t, retro_audio_sample_t, retro_environment_t, retro_input_poll_t,
+    retro_input_state_t, retro_video_refresh_t, GameInfo, SystemAvInfo, SystemInfo, API_VERSION,
+};
+
+/// Why a core could not be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The shared library could not be opened.
+    Library(libloading::Error),
+
+    /// The shared library could not be copied for reloading.
+    Copy(io::Error),
+
+    /// A mandatory entry point is not exported by the library.
+    MissingSymbol {
+        name: &'static str,
+        source: libloading::Error,
+    },
+
+    /// retro_api_version() did not return API_VERSION.
+    ApiVersionMismatch { expected: libc::c_uint, found: libc::c_uint },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Library(err) => write!(f, "failed to open core library: {}", err),
+            LoadError::Copy(err) => write!(f, "failed to copy core library for reloading: {}", err),
+            LoadError::MissingSymbol { name, .. } => write!(f, "core does not export {}", name),
+            LoadError::ApiVersionMismatch { expected, found } => {
+                write!(f, "core uses libretro API version {}, expected {}", found, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Library(err) => Some(err),
+            LoadError::Copy(err) => Some(err),
+            LoadError::MissingSymbol { source, .. } => Some(source),
+            LoadError::ApiVersionMismatch { .. } => None,
+        }
+    }
+}
+
+unsafe fn symbol<T: Copy>(library: &Library, name: &'static str) -> Result<T, LoadError> {
+    library
+        .get::<T>(name.as_bytes())
+        .map(|symbol| *symbol)
+        .map_err(|source| LoadError::MissingSymbol { name, source })
+}
+
+/// A libretro core loaded at runtime, with every mandatory entry point
+/// resolved. The function pointers are only valid while the library
+/// stays loaded, so they are kept private to this struct.
+pub struct CoreApi {
+    path: PathBuf,
+    retro_set_environment: unsafe extern "C" fn(retro_environment_t),
+    retro_set_video_refresh: unsafe extern "C" fn(retro_video_refresh_t),
+    retro_set_audio_sample: unsafe extern "C" fn(retro_audio_sample_t),
+    retro_set_audio_sample_batch: unsafe extern "C" fn(retro_audio_sample_batch_t),
+    retro_set_input_poll: unsafe extern "C" fn(retro_input_poll_t),
+    retro_set_input_state: unsafe extern "C" fn(retro_input_state_t),
+    retro_init: unsafe extern "C" fn(),
+    retro_deinit: unsafe extern "C" fn(),
+    retro_api_version: unsafe extern "C" fn() -> libc::c_uint,
+    retro_get_system_info: unsafe extern "C" fn(*mut SystemInfo),
+    retro_get_system_av_info: unsafe extern "C" fn(*mut SystemAvInfo),
+    retro_set_controller_port_device: unsafe extern "C" fn(libc::c_uint, libc::c_uint),
+    retro_reset: unsafe extern "C" fn(),
+    retro_run: unsafe extern "C" fn(),
+    retro_serialize_size: unsafe extern "C" fn() -> libc::size_t,
+    retro_serialize: unsafe extern "C" fn(*mut libc::c_void, libc::size_t) -> bool,
+    retro_unserialize: unsafe extern "C" fn(*const libc::c_void, libc::size_t) -> bool,
+    retro_cheat_reset: unsafe extern "C" fn(),
+    retro_cheat_set: unsafe extern "C" fn(libc::c_uint, bool, *const libc::c_char),
+    retro_load_game: unsafe extern "C" fn(*const GameInfo) -> bool,
+    retro_load_game_special: unsafe extern "C" fn(libc::c_uint, *const GameInfo, libc::size_t) -> bool,
+    retro_unload_game: unsafe extern "C" fn(),
+    retro_get_region: unsafe extern "C" fn() -> libc::c_uint,
+    retro_get_memory_data: unsafe extern "C" fn(libc::c_uint) -> *mut libc::c_void,
+    retro_get_memory_size: unsafe extern "C" fn(libc::c_uint) -> libc::size_t,
+    _library: Library,
+}
+
+impl CoreApi {
+    /// Opens the core at `path` and resolves its entry points.
+    ///
+    /// # Safety
+    /// Loading a library runs its initialisers, and the resolved symbols
+    /// are trusted to have the signatures declared by libretro.h.
+    pub unsafe fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let path = path.as_ref().to_path_buf();
+        let library = Library::new(&path).map_err(LoadError::Library)?;
+        CoreApi::resolve(path, library)
+    }
+
+    /// Resolves the entry points of an opened library and checks its
+    /// API version.
+    unsafe fn resolve(path: PathBuf, library: Library) -> Result<Self, LoadError> {
+        let core = CoreApi {
+            retro_set_environment: symbol(&library, "retro_set_environment")?,
+            retro_set_video_refresh: symbol(&library, "retro_set_video_refresh")?,
+            retro_set_audio_sample: symbol(&library, "retro_set_audio_sample")?,
+            retro_set_audio_sample_batch: symbol(&library, "retro_set_audio_sample_batch")?,
+            retro_set_input_poll: symbol(&library, "retro_set_input_poll")?,
+            retro_set_input_state: symbol(&library, "retro_set_input_state")?,
+            retro_init: symbol(&library, "retro_init")?,
+            retro_deinit: symbol(&library, "retro_deinit")?,
+            retro_api_version: symbol(&library, "retro_api_version")?,
+            retro_get_system_info: symbol(&library, "retro_get_system_info")?,
+            retro_get_system_av_info: symbol(&library, "retro_get_system_av_info")?,
+            retro_set_controller_port_device: symbol(&library, "retro_set_controller_port_device")?,
+            retro_reset: symbol(&library, "retro_reset")?,
+            retro_run: symbol(&library, "retro_run")?,
+            retro_serialize_size: symbol(&library, "retro_serialize_size")?,
+            retro_serialize: symbol(&library, "retro_serialize")?,
+            retro_unserialize: symbol(&library, "retro_unserialize")?,
+            retro_cheat_reset: symbol(&library, "retro_cheat_reset")?,
+            retro_cheat_set: symbol(&library, "retro_cheat_set")?,
+            retro_load_game: symbol(&library, "retro_load_game")?,
+            retro_load_game_special: symbol(&library, "retro_load_game_special")?,
+            retro_unload_game: symbol(&library, "retro_unload_game")?,
+            retro_get_region: symbol(&library, "retro_get_region")?,
+            retro_get_memory_data: symbol(&library, "retro_get_memory_data")?,
+            retro_get_memory_size: symbol(&library, "retro_get_memory_size")?,
+            path,
+            _library: library,
+        };
+        let found = core.api_version();
+        if found != API_VERSION {
+            return Err(LoadError::ApiVersionMismatch {
+                expected: API_VERSION,
+                found,
+            });
+        }
+        Ok(core)
+    }
+
+    /// Loads the core again from the same path, picking up a rebuilt
+    /// library, and closes the old one. All core state is lost: call
+    /// unload_game() and deinit() first, then set up the callbacks and
+    /// init() again. On error the old library stays loaded and `self`
+    /// is unchanged.
+    ///
+    /// The new library is opened from a temporary copy of the file, since
+    /// the dynamic loader would otherwise hand back the old mapping that
+    /// is still open. The copy is removed once opened where the platform
+    /// allows it.
+    ///
+    /// # Safety
+    /// Same as [`CoreApi::load`].
+    pub unsafe fn reload(&mut self) -> Result<(), LoadError> {
+        static RELOADS: AtomicU64 = AtomicU64::new(0);
+
+        let file_name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        let copy = std::env::temp_dir().join(format!(
+            "reload-{}-{}-{}",
+            process::id(),
+            RELOADS.fetch_add(1, Ordering::Relaxed),
+            file_name
+        ));
+        fs::copy(&self.path, &copy).map_err(LoadError::Copy)?;
+        let library = Library::new(&copy);
+        // Unix keeps the mapping alive without the file; elsewhere the
+        // loaded copy cannot be removed and is left behind.
+        let _ = fs::remove_file(&copy);
+        *self = CoreApi::resolve(self.path.clone(), library.map_err(LoadError::Library)?)?;
+        Ok(())
+    }
+
+    /// Path the core was loaded from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn set_environment(&self, cb: retro_environment_t) {
+        unsafe { (self.retro_set_environment)(cb) }
+    }
+
+    pub fn set_video_refresh(&self, cb: retro_video_refresh_t) {
+        unsafe { (self.retro_set_video_refresh)(cb) }
+    }
+
+    pub fn set_audio_sample(&self, cb: retro_audio_sample_t) {
+        unsafe { (self.retro_set_audio_sample)(cb) }
+    }
+
+    pub fn set_audio_sample_batch(&self, cb: retro_audio_sample_batch_t) {
+        unsafe { (self.retro_set_audio_sample_batch)(cb) }
+    }
+
+    pub fn set_input_poll(&self, cb: retro_input_poll_t) {
+        unsafe { (self.retro_set_input_poll)(cb) }
+    }
+
+    pub fn set_input_state(&self, cb: retro_input_state_t) {
+        unsafe { (self.retro_set_input_state)(cb) }
+    }
+
+    pub fn init(&self) {
+        unsafe { (self.retro_init)() }
+    }
+
+    pub fn deinit(&self) {
+        unsafe { (self.retro_deinit)() }
+    }
+
+    pub fn api_version(&self) -> libc::c_uint {
+        unsafe { (self.retro_api_version)() }
+    }
+
+    pub fn get_system_info(&self) -> SystemInfo {
+        let mut info = SystemInfo::default();
+        unsafe { (self.retro_get_system_info)(&mut info) };
+        info
+    }
+
+    pub fn get_system_av_info(&self) -> SystemAvInfo {
+        let mut info = SystemAvInfo::default();
+        unsafe { (self.retro_get_system_av_info)(&mut info) };
+        info
+    }
+
+    pub fn set_controller_port_device(&self, port: libc::c_uint, device: libc::c_uint) {
+        unsafe { (self.retro_set_controller_port_device)(port, device) }
+    }
+
+    pub fn reset(&self) {
+        unsafe { (self.retro_reset)() }
+    }
+
+    pub fn run(&self) {
+        unsafe { (self.retro_run)() }
+    }
+
+    pub fn serialize_size(&self) -> usize {
+        unsafe { (self.retro_serialize_size)() }
+    }
+
+    pub fn serialize(&self, data: &mut [u8]) -> bool {
+        unsafe { (self.retro_serialize)(data.as_mut_ptr().cast(), data.len()) }
+    }
+
+    pub fn unserialize(&self, data: &[u8]) -> bool {
+        unsafe { (self.retro_unserialize)(data.as_ptr().cast(), data.len()) }
+    }
+
+    pub fn cheat_reset(&self) {
+        unsafe { (self.retro_cheat_reset)() }
+    }
+
+    pub fn cheat_set(&self, index: libc::c_uint, enabled: bool, code: &CStr) {
+        unsafe { (self.retro_cheat_set)(index, enabled, code.as_ptr()) }
+    }
+
+    /// # Safety
+    /// `game` must be NULL or point to a GameInfo whose pointers are
+    /// valid for as long as the core requires them.
+    pub unsafe fn load_game(&self, game: *const GameInfo) -> bool {
+        (self.retro_load_game)(game)
+    }
+
+    /// # Safety
+    /// `info` must point to `num_info` GameInfo structs whose pointers
+    /// are valid for as long as the core requires them.
+    pub unsafe fn load_game_special(&self, game_type: libc::c_uint, info: *const GameInfo, num_info: usize) -> bool {
+        (self.retro_load_game_special)(game_type, info, num_info)
+    }
+
+    pub fn unload_game(&self) {
+        unsafe { (self.retro_unload_game)() }
+    }
+
+    pub fn get_region(&self) -> libc::c_uint {
+        unsafe { (self.retro_get_region)() }
+    }
+
+    pub fn get_memory_data(&self, id: libc::c_uint) -> *mut libc::c_void {
+        unsafe { (self.retro_get_memory_data)(id) }
+    }
+
+    pub fn get_memory_size(&self, id: libc::c_uint) -> usize {
+        unsafe { (self.retro_get_memory_size)(id) }
+    }
+}
+
+/// Core metadata a frontend queries once after retro_load_game() instead
+/// of calling into the core every time it is needed. The system info
+/// strings live in the core library, so the record borrows the core and
+/// cannot outlive it or a reload.
+#[derive(Clone, Copy, Debug)]
+pub struct CoreInfo<'a> {
+    region: libc::c_uint,
+    system_info: SystemInfo,
+    av_info: SystemAvInfo,
+    _core: PhantomData<&'a CoreApi>,
+}
+
+impl<'a> CoreInfo<'a> {
+    /// Queries region, system info and av info from a core with a game loaded.
+    pub fn query(core: &'a CoreApi) -> Self {
+        Self {
+            region: core.get_region(),
+            system_info: core.get_system_info(),
+            av_info: core.get_system_av_info(),
+            _core: PhantomData,
+        }
+    }
+
+    /// One of the REGION_* values.
+    pub fn region(&self) -> libc::c_uint {
+        self.region
+    }
+
+    pub fn system_info(&self) -> &SystemInfo {
+        &self.system_info
+    }
+
+    pub fn av_info(&self) -> &SystemAvInfo {
+        &self.av_info
+    }
+
+    /// Records the av info the core passed with ENVIRONMENT_SET_SYSTEM_AV_INFO.
+    pub fn set_av_info(&mut self, av_info: SystemAvInfo) {
+        self.av_info = av_info;
+    }
+}
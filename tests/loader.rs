@@ -0,0 +1,145 @@
+#![cfg(feature = "loader")]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use std::ffi::{c_char, CStr};
+
+use libretro_sys::{CoreApi, CoreInfo, LoadError, API_VERSION, REGION_PAL};
+
+/// Builds tests/stub_core as a cdylib named after `name`, passing `cfg`
+/// as a `--cfg` flag when given, and returns the library path.
+fn build_stub_core(name: &str, cfg: Option<&str>) -> PathBuf {
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/stub_core/lib.rs");
+    let output = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!(
+        "{}{}{}",
+        std::env::consts::DLL_PREFIX,
+        name,
+        std::env::consts::DLL_SUFFIX
+    ));
+    let mut rustc = Command::new(std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into()));
+    rustc
+        .args(["--crate-type", "cdylib", "--edition", "2021", "--crate-name", name])
+        .arg(&source)
+        .arg("-o")
+        .arg(&output);
+    if let Some(cfg) = cfg {
+        rustc.args(["--cfg", cfg]);
+    }
+    let status = rustc.status().expect("failed to run rustc");
+    assert!(status.success(), "building the stub core failed");
+    output
+}
+
+fn load_error(path: &Path) -> LoadError {
+    match unsafe { CoreApi::load(path) } {
+        Ok(_) => panic!("{} loaded", path.display()),
+        Err(err) => err,
+    }
+}
+
+#[test]
+fn load_stub_core() {
+    let path = build_stub_core("stub_core_load", None);
+    let core = unsafe { CoreApi::load(&path) }.unwrap();
+    assert_eq!(core.api_version(), API_VERSION);
+    assert_eq!(core.path(), path);
+}
+
+#[test]
+fn load_missing_library() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("no_such_core.so");
+    assert!(matches!(load_error(&path), LoadError::Library(_)));
+}
+
+#[test]
+fn load_missing_symbol() {
+    let path = build_stub_core("stub_core_missing_symbol", Some("missing_symbol"));
+    match load_error(&path) {
+        LoadError::MissingSymbol { name, .. } => assert_eq!(name, "retro_run"),
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn load_api_version_mismatch() {
+    let path = build_stub_core("stub_core_api_version_mismatch", Some("api_version_mismatch"));
+    match load_error(&path) {
+        LoadError::ApiVersionMismatch { expected, found } => {
+            assert_eq!(expected, API_VERSION);
+            assert_eq!(found, 2);
+        }
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn reload_stub_core() {
+    let path = build_stub_core("stub_core_reload", None);
+    let mut core = unsafe { CoreApi::load(&path) }.unwrap();
+    unsafe { core.reload() }.unwrap();
+    assert_eq!(core.api_version(), API_VERSION);
+    assert_eq!(core.path(), path);
+}
+
+#[test]
+fn reload_failure_keeps_core_loaded() {
+    let path = build_stub_core("stub_core_reload_failure", None);
+    let mut core = unsafe { CoreApi::load(&path) }.unwrap();
+
+    // Replace rather than overwrite the file: the old library maps it.
+    fs::remove_file(&path).unwrap();
+    build_stub_core("stub_core_reload_failure", Some("api_version_mismatch"));
+    let err = unsafe { core.reload() }.unwrap_err();
+    assert!(matches!(err, LoadError::ApiVersionMismatch { .. }), "{}", err);
+    assert_eq!(core.api_version(), API_VERSION);
+
+    fs::remove_file(&path).unwrap();
+    fs::write(&path, b"not a library").unwrap();
+    let err = unsafe { core.reload() }.unwrap_err();
+    assert!(matches!(err, LoadError::Library(_)), "{}", err);
+
+    fs::remove_file(&path).unwrap();
+    let err = unsafe { core.reload() }.unwrap_err();
+    assert!(matches!(err, LoadError::Copy(_)), "{}", err);
+    assert_eq!(core.api_version(), API_VERSION);
+    assert_eq!(core.path(), path);
+}
+
+#[test]
+fn core_info_caches_av_info() {
+    let path = build_stub_core("stub_core_info", None);
+    let core = unsafe { CoreApi::load(&path) }.unwrap();
+    let mut info = CoreInfo::query(&core);
+    assert_eq!(info.region(), REGION_PAL);
+    assert_eq!(unsafe { CStr::from_ptr(info.system_info().library_name) }, c"Stub");
+    assert_eq!(info.av_info().geometry.base_width, 256);
+
+    // The stub switches to a high resolution mode once it runs.
+    core.run();
+    assert_eq!(core.get_system_av_info().geometry.base_width, 512);
+    assert_eq!(info.av_info().geometry.base_width, 256);
+
+    info.set_av_info(core.get_system_av_info());
+    assert_eq!(info.av_info().geometry.base_width, 512);
+    assert_eq!(info.av_info().geometry.max_width, 512);
+}
+
+#[test]
+fn cheats_reach_the_core() {
+    let path = build_stub_core("stub_core_cheats", None);
+    let core = unsafe { CoreApi::load(&path) }.unwrap();
+    core.cheat_set(3, true, c"ABCD-1234");
+    core.cheat_set(7, false, c"0123:45");
+    core.cheat_reset();
+
+    let library = unsafe { libloading::Library::new(&path) }.unwrap();
+    let log = unsafe {
+        let cheat_log = library
+            .get::<unsafe extern "C" fn() -> *const c_char>(b"stub_cheat_log")
+            .unwrap();
+        CStr::from_ptr(cheat_log()).to_str().unwrap().to_owned()
+    };
+    assert_eq!(log, "set 3 true ABCD-1234\nset 7 false 0123:45\nreset\n");
+}
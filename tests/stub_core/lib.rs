@@ -0,0 +1,152 @@
+// Minimal libretro core for tests/loader.rs, which builds it as a cdylib.
+// `--cfg missing_symbol` leaves out retro_run and `--cfg api_version_mismatch`
+// reports an API version the loader does not accept. stub_cheat_log
+// exposes the cheat calls the core received.
+
+use std::ffi::{c_char, c_uint, c_void, CStr, CString};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+#[repr(C)]
+pub struct SystemInfo {
+    library_name: *const c_char,
+    library_version: *const c_char,
+    valid_extensions: *const c_char,
+    need_fullpath: bool,
+    block_extract: bool,
+}
+
+#[repr(C)]
+pub struct SystemAvInfo {
+    base_width: c_uint,
+    base_height: c_uint,
+    max_width: c_uint,
+    max_height: c_uint,
+    aspect_ratio: f32,
+    fps: f64,
+    sample_rate: f64,
+}
+
+type Callback = Option<unsafe extern "C" fn()>;
+
+// Set by retro_run so the tests can observe an av info change.
+static HIRES: AtomicBool = AtomicBool::new(false);
+
+// One line per retro_cheat_set/retro_cheat_reset call, oldest first.
+static CHEAT_LOG: Mutex<String> = Mutex::new(String::new());
+static CHEAT_LOG_C: Mutex<Option<CString>> = Mutex::new(None);
+
+/// Returns the cheat log as a C string that stays valid until the next call.
+#[no_mangle]
+pub extern "C" fn stub_cheat_log() -> *const c_char {
+    let log = CString::new(CHEAT_LOG.lock().unwrap().as_str()).unwrap();
+    let mut slot = CHEAT_LOG_C.lock().unwrap();
+    slot.insert(log).as_ptr()
+}
+
+#[no_mangle]
+pub extern "C" fn retro_api_version() -> c_uint {
+    if cfg!(api_version_mismatch) {
+        2
+    } else {
+        1
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn retro_set_environment(_cb: Callback) {}
+#[no_mangle]
+pub extern "C" fn retro_set_video_refresh(_cb: Callback) {}
+#[no_mangle]
+pub extern "C" fn retro_set_audio_sample(_cb: Callback) {}
+#[no_mangle]
+pub extern "C" fn retro_set_audio_sample_batch(_cb: Callback) {}
+#[no_mangle]
+pub extern "C" fn retro_set_input_poll(_cb: Callback) {}
+#[no_mangle]
+pub extern "C" fn retro_set_input_state(_cb: Callback) {}
+#[no_mangle]
+pub extern "C" fn retro_init() {}
+#[no_mangle]
+pub extern "C" fn retro_deinit() {}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_get_system_info(info: *mut SystemInfo) {
+    *info = SystemInfo {
+        library_name: c"Stub".as_ptr(),
+        library_version: c"1.0".as_ptr(),
+        valid_extensions: c"bin".as_ptr(),
+        need_fullpath: false,
+        block_extract: false,
+    };
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn retro_get_system_av_info(info: *mut SystemAvInfo) {
+    let scale = if HIRES.load(Ordering::SeqCst) { 2 } else { 1 };
+    *info = SystemAvInfo {
+        base_width: 256 * scale,
+        base_height: 224 * scale,
+        max_width: 512,
+        max_height: 448,
+        aspect_ratio: 4.0 / 3.0,
+        fps: 60.0,
+        sample_rate: 48000.0,
+    };
+}
+
+#[no_mangle]
+pub extern "C" fn retro_set_controller_port_device(_port: c_uint, _device: c_uint) {}
+#[no_mangle]
+pub extern "C" fn retro_reset() {}
+
+#[cfg(not(missing_symbol))]
+#[no_mangle]
+pub extern "C" fn retro_run() {
+    HIRES.store(true, Ordering::SeqCst);
+}
+
+#[no_mangle]
+pub extern "C" fn retro_serialize_size() -> usize {
+    0
+}
+#[no_mangle]
+pub extern "C" fn retro_serialize(_data: *mut c_void, _size: usize) -> bool {
+    false
+}
+#[no_mangle]
+pub extern "C" fn retro_unserialize(_data: *const c_void, _size: usize) -> bool {
+    false
+}
+#[no_mangle]
+pub extern "C" fn retro_cheat_reset() {
+    CHEAT_LOG.lock().unwrap().push_str("reset\n");
+}
+#[no_mangle]
+pub unsafe extern "C" fn retro_cheat_set(index: c_uint, enabled: bool, code: *const c_char) {
+    let code = CStr::from_ptr(code).to_string_lossy();
+    let line = format!("set {} {} {}\n", index, enabled, code);
+    CHEAT_LOG.lock().unwrap().push_str(&line);
+}
+#[no_mangle]
+pub extern "C" fn retro_load_game(_game: *const c_void) -> bool {
+    true
+}
+#[no_mangle]
+pub extern "C" fn retro_load_game_special(_game_type: c_uint, _info: *const c_void, _num_info: usize) -> bool {
+    false
+}
+#[no_mangle]
+pub extern "C" fn retro_unload_game() {}
+#[no_mangle]
+pub extern "C" fn retro_get_region() -> c_uint {
+    1
+}
+#[no_mangle]
+pub extern "C" fn retro_get_memory_data(_id: c_uint) -> *mut c_void {
+    std::ptr::null_mut()
+}
+#[no_mangle]
+pub extern "C" fn retro_get_memory_size(_id: c_uint) -> usize {
+    0
+}